@@ -3,86 +3,69 @@
 // Given a list of integers, use a vector and return the mean (average value),
 // median (when sorted, the value in the middle position), and mode
 // (the value that occurs most often) of the list.
+// The sums themselves are in `stats`, which works for any kind of number,
+// so the list can also come from the command line, decimals and all:
+//
+//     collections average 2.5 4 4 10
 // ===========================================================================
-use std::collections::HashMap;
 
-pub fn main() {
-	// Creating a list of integers
-	let list = vec![6, 3, 4, 0, 2, 1, 6, 2];
-
-	let mean = mean(&list);
+use std::fmt::Display;
+use std::process;
+
+use crate::stats::{self, Number};
+
+pub const USAGE: &str = "Usage: collections average [<number> ...]";
+
+pub fn main(args: impl Iterator<Item = String>) {
+	let numbers: Result<Vec<f64>, String> = args
+		.map(|arg| arg.parse().map_err(|_| format!("'{}' is not a number", arg)))
+		.collect();
+	let numbers = numbers.unwrap_or_else(|err| {
+		eprintln!("Problem parsing arguments: {}", err);
+		eprintln!("{}", USAGE);
+		process::exit(1);
+	});
+
+	if numbers.is_empty() {
+		// Creating a list of integers
+		let list = vec![6, 3, 4, 0, 2, 1, 6, 2];
+		describe(&list);
+	} else {
+		describe(&numbers);
+	}
+}
 
-	let median = median(&list);
+fn describe<T: Number + Display>(list: &[T]) {
+	let Some(mean) = stats::mean(list) else {
+		println!("There are no numbers to average");
+		return;
+	};
 
-	let mode = mode(&list);
+	let values: Vec<String> = list.iter().map(|value| value.to_string()).collect();
+	let mode: Vec<String> = stats::mode(list)
+		.unwrap_or_default()
+		.iter()
+		.map(|value| value.to_string())
+		.collect();
 
+	println!("The list is: [{}]", values.join(", "));
 	println!("The mean is: {}", mean);
-	println!("The median is: {}", median);
-	println!("The mode is: {:?}", mode);
-
-	println!("{:?}", &list);
-}
-
-fn mean(v: &Vec<i32>) -> f32 {
-	// adding all the values together
-	let mut sum = 0;
-	for i in v {
-		sum += i;
+	println!("The median is: {}", show(stats::median(list)));
+	println!("The mode is: {}", mode.join(", "));
+	println!("The variance is: {}", show(stats::variance(list)));
+	println!("The standard deviation is: {}", show(stats::std_dev(list)));
+	println!("The smallest is: {}", show(stats::min(list)));
+	println!("The largest is: {}", show(stats::max(list)));
+	println!("The range is: {}", show(stats::range(list)));
+	if let Some(quartiles) = stats::quartiles(list) {
+		println!("The quartiles are: {}, {}, {}", quartiles.lower, quartiles.median, quartiles.upper);
 	}
-
-	// get the length of the vector
-	let len = v.len() as f32;
-
-	// divide the total by the length
-	sum as f32 / len
-}
-
-fn median(v: &Vec<i32>) -> i32 {
-	// making a new vector to sort
-	let mut temp: Vec<i32> = Vec::new();
-
-	// deep copy
-	for i in v {
-		temp.push(*i);
+	match stats::percentile(list, 90.0) {
+		Ok(percentile) => println!("The 90th percentile is: {}", percentile),
+		Err(e) => println!("There's no 90th percentile: {}", e),
 	}
-
-	// sort
-	temp.sort();
-
-	// get length
-	let len = temp.len();
-
-	// match temp.get(len / 2usize) {
-	// 	Some(median) => median,
-	// 	None => 0,
-	// }
-
-	temp[len/2usize]
 }
 
-fn mode(v: &Vec<i32>) -> Vec<i32> {
-	// creating a hashmap to store frequency of values
-	let mut frequency: HashMap<&i32, i32> = HashMap::new();
-
-	// record frequency of values
-	for i in v {
-		let count = frequency.entry(i).or_insert(0);
-		*count += 1;
-	}
-
-	// making a vector to store the most appeared values
-	let mut max = 0;
-	let mut mode: Vec<i32> = Vec::new();
-
-	for (key, value) in &frequency {
-		if *value > max {
-			max = *value;
-			mode = Vec::new();
-			mode.push(**key);
-		} else if *value == max {
-			mode.push(**key);
-		}	
-	}
-	
-	mode
-}
\ No newline at end of file
+fn show(value: Option<impl Display>) -> String {
+	value.map_or_else(|| String::from("none"), |value| value.to_string())
+}
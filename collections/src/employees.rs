@@ -7,152 +7,519 @@
 // or all people in the company by department, sorted alphabetically.
 // ===================================================================
 
-use std::io;
-use std::collections::HashMap;
+use std::error::Error;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod access;
+mod command;
+mod company;
+mod date;
+mod history;
+mod http;
+mod query;
+mod report;
+mod search;
+mod sqlite;
+mod storage;
+mod store;
+
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
+
+use access::Access;
+use command::Command;
+use company::{Company, CompanyError, Orphans};
+use history::{EventKind, Journal};
+use query::Filter;
+use report::Format;
+use search::Completer;
+use sqlite::SqliteStore;
+use storage::StoreError;
+use store::{DirectoryStore, FileStore, MemoryStore};
+
+const HELP: &str = "Commands:
+  Add <name> to <department>
+  Add #<id> to <department>
+  Remove <name> [from <department>]
+  Move <name> [from <department>] to <department>
+  List <department or all> [hired after|before <date>] [where <field>=<value> and ...]
+  Group by <field> [in <department>] [hired ...] [where ...]
+  Show <name>
+  Set <name> <field> to <value>
+  Unset <name> <field>
+  Roster [text, markdown or html]
+  Find <start of a name>
+  Create <department>
+  Rename <department> to <new name>
+  Merge <department> into <department>
+  Delete <department> [keep, remove or move to <department>]
+  Limit <department> to <number of people or none>
+  Report <name> to <manager or nobody>
+  Chain <name>
+  Reports <name>
+  Chart
+  Import <file.csv or file.json>
+  Export <file.csv or file.json>
+  Undo
+  Redo
+  History
+  Help
+  Quit
+Put names with spaces in quotes: Add \"Amir Khan\" to \"Customer Support\".
+Use #<id> instead of a name when several employees share it.
+Fields are title, start (a date like 2024-03-01), email, location,
+or any other name for an attribute of your own.
+Deleting a department with people in no other department needs to say
+whether to keep them without one, remove them, or move them elsewhere.
+Tab completes commands, names and departments.";
+
+pub const USAGE: &str = "Usage: collections employees [--file <path> | --db <path> | --memory] \
+	[--users <path>] [--serve <port> | --format <text|markdown|html>]";
+
+// Where the directory is kept between runs.
+enum Storage {
+	// A JSON save file with its history log next to it.
+	File(PathBuf),
+	// A SQLite database.
+	Sqlite(PathBuf),
+	Memory,
+}
 
-pub fn main() {
-	let mut book: HashMap<String, String> = HashMap::new();
-	// interface to allow choice of adding or displaying
-	loop {
-		println!(
-			"=================================\n\
-			1. Add an employee\n\
-			2. Display employees\n\
-			3. Quit\n\
-			=================================\n"
-		);
-
-		let mut choice = String::new();
-
-		io::stdin()
-			.read_line(&mut choice)
-			.expect("Failed to read line");
-
-		let choice = match choice.trim().parse::<i8>() {
-			Ok(num) => num,
-			Err(_) => continue,
-		};
+impl Storage {
+	fn open(&self) -> Result<Box<dyn DirectoryStore>, StoreError> {
+		Ok(match self {
+			Storage::File(path) => Box::new(FileStore::new(path)),
+			Storage::Sqlite(path) => Box::new(SqliteStore::open(path)?),
+			Storage::Memory => Box::new(MemoryStore::default()),
+		})
+	}
+}
 
-		match choice {
-			1 => {
-				let (key, value) = get_info();
-				book.insert(key, value);
-			},
-			2 => {
-				loop {
-					println!(
-						"==============================\n\
-						1. List employees in department\n\
-						2. List all employees\n\
-						3. Back to menu\n\
-						==============================\n"
-					);
-
-					let mut choice = String::new();
-
-					io::stdin()
-						.read_line(&mut choice)
-						.expect("Unable to read line");
-
-					match choice.trim().parse::<i32>() {
-						Ok(1) => department_list(&book),
-						Ok(2) => full_list(&book),
-						_ => break,
-					}
-				}
-			},
-			_ => break,
+// Everything that was asked for on the command line.
+struct Options {
+	storage: Storage,
+	// The roles file, when one was named instead of the usual one.
+	users: Option<PathBuf>,
+	// Run the HTTP service on this port instead of the text interface.
+	serve: Option<u16>,
+	// Print the roster in this format instead of the text interface.
+	format: Option<Format>,
+}
+
+impl Options {
+	fn build(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+		let mut storage = Storage::File(storage::default_path());
+		let mut users = None;
+		let mut serve = None;
+		let mut format = None;
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--file" | "-f" => storage = Storage::File(PathBuf::from(flag_value(&arg, args.next())?)),
+				"--db" => storage = Storage::Sqlite(PathBuf::from(flag_value(&arg, args.next())?)),
+				"--memory" => storage = Storage::Memory,
+				"--users" => users = Some(PathBuf::from(flag_value(&arg, args.next())?)),
+				"--serve" => {
+					let port = flag_value(&arg, args.next())?;
+					let port = port
+						.parse()
+						.map_err(|_| format!("--serve expects a port number, got '{}'", port))?;
+					serve = Some(port);
+				},
+				"--format" => {
+					let name = flag_value(&arg, args.next())?;
+					format = Some(Format::from_name(&name).ok_or_else(|| {
+						format!("unknown format '{}' (expected text, markdown or html)", name)
+					})?);
+				},
+				_ => return Err(format!("unknown argument '{}'", arg)),
+			}
 		}
+
+		if serve.is_some() && format.is_some() {
+			return Err(String::from("--serve and --format can't be used together"));
+		}
+
+		Ok(Options {
+			storage,
+			users,
+			serve,
+			format,
+		})
 	}
 }
 
-fn get_info() -> (String, String) {
-	println!("Enter employee name:");
-
-	let mut name = String::new();
-	
-	io::stdin()
-		.read_line(&mut name)
-		.expect("Unable to read name");
+fn flag_value(flag: &str, value: Option<String>) -> Result<String, String> {
+	value.ok_or_else(|| format!("{} needs a value", flag))
+}
 
-	let name = String::from(name.trim());
+pub fn main(args: impl Iterator<Item = String>) {
+	let options = Options::build(args).unwrap_or_else(|err| {
+		eprintln!("Problem parsing arguments: {}", err);
+		eprintln!("{}", USAGE);
+		process::exit(1);
+	});
+
+	// Better to stop than to start empty and save over a file we couldn't read.
+	let mut company = Company::new();
+	let journal = options.storage.open().and_then(|store| Journal::open(store, &mut company));
+	let mut journal = journal.unwrap_or_else(|err| {
+		eprintln!("Couldn't load the directory: {}", err);
+		process::exit(1);
+	});
+
+	// A roles file that was asked for by name has to be there; without the
+	// usual one, everyone is a viewer until somebody writes it.
+	let users = match options.users {
+		Some(users) if !users.exists() => {
+			eprintln!("There's no users file at {}", users.display());
+			process::exit(1);
+		},
+		Some(users) => users,
+		None => storage::default_users_path(),
+	};
+	if !users.exists() {
+		eprintln!("No roles in {} yet, so nobody can change the directory", users.display());
+	}
+	let access = Access::load(&users, access::current_user()).unwrap_or_else(|err| {
+		eprintln!("Couldn't load the users in {}: {}", users.display(), err);
+		process::exit(1);
+	});
+
+	if let Some(format) = options.format {
+		print!("{}", report::roster(&company, &Filter::default(), format));
+		return;
+	}
 
-	println!("Enter employee department:");
+	if let Some(port) = options.serve {
+		let listener = TcpListener::bind(("127.0.0.1", port)).unwrap_or_else(|err| {
+			eprintln!("Couldn't listen on port {}: {}", port, err);
+			process::exit(1);
+		});
+		println!("Serving the directory on http://127.0.0.1:{}", port);
 
-	let mut department = String::new();
+		if let Err(err) = http::serve(listener, &mut company, &mut journal, &access) {
+			eprintln!("The server stopped: {}", err);
+			process::exit(1);
+		}
+		return;
+	}
 
-	io::stdin()
-		.read_line(&mut department)
-		.expect("Unable to read department");
+	println!("{}", HELP);
+	println!("{}", journal.describe());
+	println!("{}", access.describe());
 
-	let department = String::from(department.trim());
+	let mut editor: Editor<Completer, DefaultHistory> = Editor::new().expect("Unable to start the prompt");
+	let mut completer = Completer::default();
+	completer.update(&company);
+	editor.set_helper(Some(completer));
 
-	(name, department)
-}
+	loop {
+		let line = match editor.readline("> ") {
+			Ok(line) => line,
+			// Ctrl-C clears the line, and end of input works like Quit
+			Err(ReadlineError::Interrupted) => continue,
+			Err(ReadlineError::Eof) => break,
+			Err(e) => {
+				eprintln!("Unable to read line: {}", e);
+				break;
+			},
+		};
 
-fn department_list(book: &HashMap<String, String>) {
-	println!("Enter the department:");
+		let line = line.trim();
+		if line.is_empty() {
+			continue;
+		}
+		let _ = editor.add_history_entry(line);
+
+		let command = match command::parse(line) {
+			Ok(command) => command,
+			Err(e) => {
+				println!("  {}", line);
+				for caret_line in e.caret().lines() {
+					println!("  {}", caret_line);
+				}
+				continue;
+			},
+		};
 
-	let mut department = String::new();
+		if let Err(e) = access.check(access::required(&command, &journal), line) {
+			println!("Sorry, {}.", e);
+			continue;
+		}
 
-	io::stdin()
-		.read_line(&mut department)
-		.expect("Unable to read department");
+		let events = journal.events().len();
+		let result = run(&mut company, &mut journal, command);
 
-	department = String::from(department.trim());
+		// A command that went wrong half way may still have changed something.
+		// Whatever couldn't be saved has been taken back again.
+		match journal.record(&mut company, line) {
+			Ok(_) => {},
+			Err(e @ StoreError::Conflict) => {
+				println!("Couldn't save the change: {}", e);
+				break;
+			},
+			Err(e) => println!("Couldn't save the change: {}", e),
+		}
 
-	let mut names: Vec<&String> = Vec::new();
+		if journal.events().len() != events {
+			if let Some(completer) = editor.helper_mut() {
+				completer.update(&company);
+			}
+		}
 
-	for (key, value) in book {
-		if department.eq(value) {
-			names.push(key);
+		match result {
+			Ok(true) => {},
+			Ok(false) => break,
+			Err(e) => println!("Sorry, {}.", e),
 		}
 	}
+}
 
-	names.sort();
+// Carries out one command. Returns false when it's time to quit.
+fn run(company: &mut Company, journal: &mut Journal, command: Command) -> Result<bool, Box<dyn Error>> {
+	match command {
+		Command::Add { employee, department } => {
+			let close = close_department(company, &department);
+			let id = if employee.starts_with('#') {
+				let id = company.lookup(&employee)?;
+				company.assign(id, &department)?;
+				id
+			} else {
+				company.add(&employee, &department)?
+			};
+			println!("Added {} to {}", company.get(id).unwrap(), department);
+			note_new_department(&department, close);
+		},
+		Command::Remove { employee, department: Some(department) } => {
+			let id = company.lookup(&employee)?;
+			company.unassign(id, &department)?;
+			println!("Removed {} from {}", company.get(id).unwrap(), department);
+		},
+		Command::Remove { employee, department: None } => {
+			let id = company.lookup(&employee)?;
+			let employee = company.remove(id)?;
+			println!("Removed {}", employee);
+		},
+		Command::Move { employee, from, to } => {
+			let id = company.lookup(&employee)?;
+			let departments = &company.get(id).unwrap().departments;
+
+			let from = match from {
+				Some(from) => Some(from),
+				None if departments.len() > 1 => return Err(CompanyError::SeveralDepartments(id).into()),
+				None => departments.iter().next().cloned(),
+			};
+
+			let close = close_department(company, &to);
+			match from {
+				Some(from) => company.transfer(id, &from, &to)?,
+				None => company.assign(id, &to)?,
+			}
+			println!("Moved {} to {}", company.get(id).unwrap(), to);
+			note_new_department(&to, close);
+		},
+		Command::List { department: Some(department), filter } => department_list(company, &department, &filter),
+		Command::List { department: None, filter } => print!("{}", report::roster(company, &filter, Format::Text)),
+		Command::Group { field, department, filter } => {
+			let employees = match &department {
+				Some(department) => company.list_department(department),
+				None => company.employees(),
+			};
+
+			for (value, members) in query::group_by(filter.apply(employees), &field) {
+				let value = value.unwrap_or_else(|| format!("No {}", field));
+				println!("{} ({})", value, members.len());
+				for employee in members {
+					println!("  {}", employee);
+				}
+			}
+		},
+		Command::Show(employee) => {
+			let id = company.lookup(&employee)?;
+			show(company, id);
+		},
+		Command::Set { employee, field, value } => {
+			let id = company.lookup(&employee)?;
+			company.set_field(id, &field, value.as_deref())?;
+			show(company, id);
+		},
+		Command::Roster(name) => {
+			let format = Format::from_name(&name)
+				.ok_or_else(|| format!("there's no {} format, try text, markdown or html", name))?;
+			print!("{}", report::roster(company, &Filter::default(), format));
+		},
+		Command::Find(prefix) => {
+			let found = search::find_prefix(company, &prefix);
+			if found.is_empty() {
+				println!("No one's name starts with {}", prefix);
+			}
+			for employee in found {
+				println!("  {}", employee);
+			}
+		},
+		Command::Create(department) => {
+			company.create_department(&department)?;
+			println!("Created {}", department);
+		},
+		Command::Rename { department, to } => {
+			company.rename_department(&department, &to)?;
+			println!("Renamed {} to {}", department, to);
+		},
+		Command::Merge { department, into } => {
+			let joined = company.merge_departments(&department, &into)?;
+			println!("Merged {} into {}, {} {} joined", department, into, joined, company::people(joined));
+		},
+		Command::Delete { department, orphans } => {
+			let orphaned = company.delete_department(&department, &orphans)?.len();
+			println!("Deleted {}", department);
+
+			let people = format!("{} {}", orphaned, company::people(orphaned));
+			match orphans {
+				_ if orphaned == 0 => {},
+				Orphans::MoveTo(to) => println!("Moved {} to {}", people, to),
+				Orphans::Remove => println!("Removed {} from the company", people),
+				_ => println!("Kept {} without a department", people),
+			}
+		},
+		Command::Limit { department, limit: Some(limit) } => {
+			company.set_limit(&department, Some(limit))?;
+			println!("{} can now have at most {} {}", department, limit, company::people(limit));
+		},
+		Command::Limit { department, limit: None } => {
+			company.set_limit(&department, None)?;
+			println!("{} no longer has a limit", department);
+		},
+		Command::Report { employee, manager } => {
+			let id = company.lookup(&employee)?;
+			let manager = match manager {
+				Some(manager) => Some(company.lookup(&manager)?),
+				None => None,
+			};
+			company.set_manager(id, manager)?;
+
+			match manager.and_then(|manager| company.get(manager)) {
+				Some(manager) => println!("{} now reports to {}", company.get(id).unwrap(), manager),
+				None => println!("{} now reports to nobody", company.get(id).unwrap()),
+			}
+		},
+		Command::Chain(employee) => {
+			let id = company.lookup(&employee)?;
+			let mut chain = vec![company.get(id).unwrap()];
+			chain.extend(company.chain(id));
+
+			let chain: Vec<String> = chain.iter().map(|employee| employee.to_string()).collect();
+			println!("{}", chain.join(" -> "));
+		},
+		Command::Reports(employee) => {
+			let id = company.lookup(&employee)?;
+			let employee = company.get(id).unwrap();
+
+			for (heading, reports) in [
+				(format!("Direct reports of {}", employee), company.direct_reports(id)),
+				(format!("Everyone under {}", employee), company.all_reports(id)),
+			] {
+				println!("{} ({})", heading, reports.len());
+				for report in reports {
+					println!("  {}", report);
+				}
+			}
+		},
+		Command::Chart => {
+			for (depth, employee) in company.org_chart() {
+				let departments: Vec<&str> = employee.departments.iter().map(|d| d.as_str()).collect();
+				println!("{}{} [{}]", "    ".repeat(depth), employee, departments.join(", "));
+			}
+		},
+		Command::Import(file) => {
+			let count = storage::import(company, Path::new(&file)).map_err(|e| format!("{}: {}", file, e))?;
+			println!("Imported {} employees from {}", count, file);
+		},
+		Command::Export(file) => {
+			let count = storage::export(company, Path::new(&file)).map_err(|e| format!("{}: {}", file, e))?;
+			println!("Exported {} employees to {}", count, file);
+		},
+		Command::Undo => match journal.undo(company)? {
+			Some(action) => println!("Undid {}", action),
+			None => println!("Nothing to undo"),
+		},
+		Command::Redo => match journal.redo(company)? {
+			Some(action) => println!("Redid {}", action),
+			None => println!("Nothing to redo"),
+		},
+		Command::History => print_history(journal),
+		Command::Help => println!("{}", HELP),
+		Command::Quit => return Ok(false),
+	}
 
-	println!("{:#?}", names);
+	Ok(true)
 }
 
-fn full_list(book: &HashMap<String, String>) {
-	// loop through HashMap to find all types of values, sorted in a vector
-	let mut departments: Vec<String> = Vec::new();
+fn print_history(journal: &Journal) {
+	if journal.is_empty() {
+		println!("Nothing has changed yet");
+		return;
+	}
 
+	for (number, event) in journal.events().iter().enumerate() {
+		let action = match event.kind {
+			EventKind::Change => event.action.clone(),
+			kind => format!("{} {}", history::kind_name(kind), event.action),
+		};
+		println!("{:>4}  {}  {}", number + 1, date::format_timestamp(event.at), action);
 
-	// create a vector for each department found
-	for (_key, value) in book {
-		if !departments.contains(value) {
-			departments.push(String::from(&value[..]));
+		for change in &event.changes {
+			println!("        {}", change);
 		}
 	}
+}
 
-	let mut employees: Vec<Vec<String>> = Vec::new();
+// A department that doesn't exist yet but is spelled a lot like one that
+// does, so a typo doesn't quietly start a new department.
+fn close_department(company: &Company, department: &str) -> Option<String> {
+	let departments = company.department_names();
+	if departments.contains(&department) {
+		return None;
+	}
+	search::suggest(department, departments).map(String::from)
+}
 
-	for _i in 0..departments.len() {
-		employees.push(Vec::new());
+fn note_new_department(department: &str, close: Option<String>) {
+	if let Some(close) = close {
+		println!("Note: {} is a new department. Did you mean {}?", department, close);
 	}
+}
 
-	// loop through HashMap again and store each employee in corresponding vector
-	for (key, value) in book {
-		let mut count: usize = 0;
-		for department in &departments {
-			if department.eq(value) {
-				&mut employees[count].push(String::from(&key[..]));
-			}
-			count += 1;
-		}
+fn show(company: &Company, id: company::EmployeeId) {
+	let employee = company.get(id).unwrap();
+	println!("{}", employee);
+
+	let departments: Vec<&str> = employee.departments.iter().map(|department| department.as_str()).collect();
+	let manager = employee.manager.and_then(|manager| company.get(manager));
+
+	let mut lines = vec![
+		(String::from("departments"), departments.join(", ")),
+		(String::from("manager"), manager.map_or(String::from("nobody"), |manager| manager.to_string())),
+	];
+	lines.extend(employee.details.fields());
+
+	let width = lines.iter().map(|(field, _)| field.chars().count()).max().unwrap_or(0);
+	for (field, value) in lines {
+		println!("  {:<width$}  {}", field, value, width = width);
 	}
+}
 
-	// print department name followed by list of employees
-	let mut count: usize = 0;
-	for department in &departments {
-		println!{"Employees in {}:", department};
-		&mut employees[count].sort();
-
-		// for employee in &employees[count] {
-		// 	println!("-- {}", employee);
-		// }
-		println!("{:#?}", &employees[count]);
-		count += 1;
+fn department_list(company: &Company, department: &str, filter: &Filter) {
+	if company.has_department(department) {
+		print!("{}", report::department(company, department, filter, Format::Text));
+		return;
 	}
-}
\ No newline at end of file
+
+	match search::suggest(department, company.department_names()) {
+		Some(close) => println!("There is no department called {}. Did you mean {}?", department, close),
+		None => println!("There is no department called {}.", department),
+	}
+}
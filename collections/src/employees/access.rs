@@ -0,0 +1,266 @@
+// access:
+
+// Who may change the directory. A JSON file gives each user a role,
+//
+//     {"users": {"sally": "admin", "amir": "editor", "kim": "viewer"}}
+//
+// and every command needs a role of at least a certain level: looking
+// things up is open to anyone, changing people needs an editor, and
+// deleting departments needs an admin. Users are who the system says is
+// logged in, and anyone who isn't in the file, or any user at all when
+// there's no file yet, is a viewer.
+// The user name comes from `$USER` (or `$USERNAME`), which anyone can set
+// to anything, so roles keep honest people from slipping up. They aren't
+// a security boundary; file permissions on the directory are.
+// Whatever is turned down is added to a log next to the file, one JSON
+// object per line, `users.json` getting `denied.jsonl`, unless the file
+// names a `log` of its own.
+// ====================================================================
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::command::Command;
+use super::company::Change;
+use super::date;
+use super::history::Journal;
+use super::storage::StoreError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+	Viewer,
+	Editor,
+	Admin,
+}
+
+impl fmt::Display for Role {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Role::Viewer => write!(f, "viewer"),
+			Role::Editor => write!(f, "editor"),
+			Role::Admin => write!(f, "admin"),
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct Config {
+	users: HashMap<String, Role>,
+	#[serde(default)]
+	log: Option<PathBuf>,
+}
+
+// What goes in the log for each denied operation.
+#[derive(Serialize)]
+struct DeniedEntry<'a> {
+	// Seconds since 1970-01-01 UTC.
+	at: u64,
+	user: &'a str,
+	role: Role,
+	needed: Role,
+	action: &'a str,
+}
+
+#[derive(Debug)]
+pub struct Denied {
+	pub user: String,
+	pub role: Role,
+	pub needed: Role,
+}
+
+impl fmt::Display for Denied {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let allowed = match self.needed {
+			Role::Viewer => "anyone",
+			Role::Editor => "editors and admins",
+			Role::Admin => "admins",
+		};
+		let article = if self.role == Role::Viewer { "a" } else { "an" };
+		write!(f, "{} is {} {}, and only {} can do that", self.user, article, self.role, allowed)
+	}
+}
+
+impl std::error::Error for Denied {}
+
+pub struct Access {
+	user: String,
+	role: Role,
+	// Where denied operations go.
+	log: PathBuf,
+}
+
+impl Access {
+	// Reads the roles in `path` for `user`. A missing file gives nobody a
+	// role, so everyone can only look things up.
+	pub fn load(path: &Path, user: String) -> Result<Access, StoreError> {
+		let config = match fs::read_to_string(path) {
+			Ok(json) => serde_json::from_str(&json)?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Config {
+				users: HashMap::new(),
+				log: None,
+			},
+			Err(e) => return Err(e.into()),
+		};
+
+		let role = config.users.get(&user).copied().unwrap_or(Role::Viewer);
+		let log = config.log.unwrap_or_else(|| path.with_file_name("denied.jsonl"));
+
+		Ok(Access {
+			user,
+			role,
+			log,
+		})
+	}
+
+	// Who is using the directory, for telling them.
+	pub fn describe(&self) -> String {
+		format!("Signed in as {} ({})", self.user, self.role)
+	}
+
+	// Lets `action` through if the user's role is at least `needed`, and
+	// logs it otherwise.
+	pub fn check(&self, needed: Role, action: &str) -> Result<(), Denied> {
+		if self.role >= needed {
+			return Ok(());
+		}
+
+		if let Err(e) = self.log_denied(needed, action) {
+			eprintln!("Couldn't log the denied {}: {}", action, e);
+		}
+
+		Err(Denied {
+			user: self.user.clone(),
+			role: self.role,
+			needed,
+		})
+	}
+
+	fn log_denied(&self, needed: Role, action: &str) -> Result<(), StoreError> {
+		let path = &self.log;
+		if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+			fs::create_dir_all(dir)?;
+		}
+
+		let entry = DeniedEntry {
+			at: date::now(),
+			user: &self.user,
+			role: self.role,
+			needed,
+			action,
+		};
+		let line = serde_json::to_string(&entry)?;
+		let mut log = OpenOptions::new().create(true).append(true).open(path)?;
+		writeln!(log, "{}", line)?;
+		Ok(())
+	}
+}
+
+// The role a command needs. Undo only ever deletes departments the
+// change it takes back had created, so an editor can undo an `Add` that
+// made one. Redo needs an admin when it would delete a department that
+// was there before, as the change it plays again did.
+pub fn required(command: &Command, journal: &Journal) -> Role {
+	match command {
+		Command::List { .. }
+		| Command::Group { .. }
+		| Command::Show(_)
+		| Command::Roster(_)
+		| Command::Find(_)
+		| Command::Chain(_)
+		| Command::Reports(_)
+		| Command::Chart
+		| Command::Export(_)
+		| Command::History
+		| Command::Help
+		| Command::Quit => Role::Viewer,
+		Command::Add { .. }
+		| Command::Remove { .. }
+		| Command::Move { .. }
+		| Command::Set { .. }
+		| Command::Report { .. }
+		| Command::Import(_)
+		| Command::Create(_)
+		| Command::Rename { .. }
+		| Command::Limit { .. }
+		| Command::Undo => Role::Editor,
+		// A merge deletes the department it empties.
+		Command::Delete { .. } | Command::Merge { .. } => Role::Admin,
+		Command::Redo => match journal.next_redo() {
+			Some(event) if deletes_a_department(&event.changes) => Role::Admin,
+			_ => Role::Editor,
+		},
+	}
+}
+
+// Whether making `changes` deletes a department that was there before
+// they started, rather than one they made along the way.
+fn deletes_a_department(changes: &[Change]) -> bool {
+	let mut created = HashSet::new();
+
+	for change in changes {
+		match change {
+			Change::DepartmentCreated { department } => {
+				created.insert(department);
+			},
+			Change::DepartmentRenamed { from, to } if created.remove(from) => {
+				created.insert(to);
+			},
+			Change::DepartmentDeleted { department } if !created.contains(department) => return true,
+			_ => {},
+		}
+	}
+
+	false
+}
+
+// Whoever is logged in, going by the environment, which they can change.
+pub fn current_user() -> String {
+	["USER", "USERNAME"]
+		.iter()
+		.find_map(|name| env::var(name).ok().filter(|user| !user.is_empty()))
+		.unwrap_or_else(|| String::from("unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::employees::company::{Company, Orphans};
+	use crate::employees::store::MemoryStore;
+
+	fn journal(company: &mut Company) -> Journal {
+		Journal::open(Box::new(MemoryStore::default()), company).unwrap()
+	}
+
+	#[test]
+	fn undoing_an_add_that_made_a_department_needs_an_editor() {
+		let mut company = Company::new();
+		let mut journal = journal(&mut company);
+		company.add("Sally", "Eng").unwrap();
+		journal.record(&mut company, "Add Sally to Eng").unwrap();
+
+		assert_eq!(required(&Command::Undo, &journal), Role::Editor);
+		journal.undo(&mut company).unwrap();
+		assert_eq!(required(&Command::Redo, &journal), Role::Editor);
+	}
+
+	#[test]
+	fn redoing_a_delete_needs_an_admin() {
+		let mut company = Company::new();
+		let mut journal = journal(&mut company);
+		company.create_department("Ops").unwrap();
+		journal.record(&mut company, "Create Ops").unwrap();
+		company.delete_department("Ops", &Orphans::Refuse).unwrap();
+		journal.record(&mut company, "Delete Ops").unwrap();
+
+		assert_eq!(required(&Command::Undo, &journal), Role::Editor);
+		journal.undo(&mut company).unwrap();
+		assert_eq!(required(&Command::Redo, &journal), Role::Admin);
+	}
+}
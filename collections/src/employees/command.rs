@@ -0,0 +1,476 @@
+// command:
+
+// Turns a line like `Add Sally to Engineering` into a `Command`.
+// Keywords are case-insensitive. A name or department with spaces in it
+// goes in double quotes, `Add "Amir Khan" to "Customer Support"`, and
+// quoting a word also stops it being read as a keyword.
+// `List` and `Group` can be narrowed down with `hired after <date>`,
+// `hired before <date>` and `where <field>=<value> and ...`.
+// ====================================================================
+
+use std::fmt;
+
+use super::company::Orphans;
+use super::date::Date;
+use super::query::Filter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+	// A name adds a new employee; `#id` puts an existing one in another department.
+	Add { employee: String, department: String },
+	// Without a department the employee leaves the company.
+	Remove { employee: String, department: Option<String> },
+	// `from` can be left out when the employee is only in one department.
+	Move { employee: String, from: Option<String>, to: String },
+	// Everyone when there's no department.
+	List { department: Option<String>, filter: Filter },
+	Group { field: String, department: Option<String>, filter: Filter },
+	Show(String),
+	// A value of `None` clears the field.
+	Set { employee: String, field: String, value: Option<String> },
+	// The roster in a format by name; text when none is given.
+	Roster(String),
+	// Employees whose name, or a word of it, starts with this.
+	Find(String),
+	// A department with nobody in it yet.
+	Create(String),
+	Rename { department: String, to: String },
+	// Everyone in `department` joins `into`, and `department` is deleted.
+	Merge { department: String, into: String },
+	Delete { department: String, orphans: Orphans },
+	// A limit of `None` lifts the cap.
+	Limit { department: String, limit: Option<usize> },
+	// A manager of `None` means the employee reports to nobody.
+	Report { employee: String, manager: Option<String> },
+	Chain(String),
+	Reports(String),
+	Chart,
+	// The format comes from the file extension, .csv or .json.
+	Import(String),
+	Export(String),
+	Undo,
+	Redo,
+	History,
+	Help,
+	Quit,
+}
+
+// Where in the line things went wrong, counted in characters so the
+// caret lines up under the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+	pub message: String,
+	pub column: usize,
+	pub width: usize,
+}
+
+impl ParseError {
+	fn at(token: &Token, message: String) -> ParseError {
+		ParseError {
+			message,
+			column: token.column,
+			width: token.width,
+		}
+	}
+
+	// Two lines to print under the input: a caret under the bad token
+	// and the message itself.
+	pub fn caret(&self) -> String {
+		format!("{}{}\n{}", " ".repeat(self.column), "^".repeat(self.width.max(1)), self.message)
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} (at column {})", self.message, self.column + 1)
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+struct Token {
+	text: String,
+	quoted: bool,
+	column: usize,
+	width: usize,
+}
+
+impl Token {
+	fn is_keyword(&self, keyword: &str) -> bool {
+		!self.quoted && self.text.eq_ignore_ascii_case(keyword)
+	}
+
+	fn describe(&self) -> String {
+		if self.quoted {
+			format!("\"{}\"", self.text)
+		} else {
+			format!("`{}`", self.text)
+		}
+	}
+}
+
+pub fn parse(line: &str) -> Result<Command, ParseError> {
+	let tokens = tokenize(line)?;
+	let mut tokens = Tokens {
+		tokens: tokens.iter(),
+		end: line.chars().count(),
+	};
+
+	let verb = match tokens.next() {
+		Some(verb) => verb,
+		None => return Err(tokens.missing("a command such as `Add`, `Remove`, `Move` or `List`")),
+	};
+
+	let command = if verb.is_keyword("add") {
+		let employee = tokens.word("a name")?;
+		tokens.keyword("to")?;
+		let department = tokens.word("a department")?;
+		Command::Add { employee, department }
+	} else if verb.is_keyword("remove") {
+		let employee = tokens.word("a name")?;
+		let department = if tokens.optional_keyword("from") {
+			Some(tokens.word("a department")?)
+		} else {
+			None
+		};
+		Command::Remove { employee, department }
+	} else if verb.is_keyword("move") {
+		let employee = tokens.word("a name")?;
+		let from = if tokens.optional_keyword("from") {
+			Some(tokens.word("a department")?)
+		} else {
+			None
+		};
+		tokens.keyword("to")?;
+		let to = tokens.word("a department")?;
+		Command::Move { employee, from, to }
+	} else if verb.is_keyword("list") {
+		let department = match tokens.next() {
+			Some(token) if token.is_keyword("all") => None,
+			Some(token) => Some(token.text.clone()),
+			None => return Err(tokens.missing("a department or `all`")),
+		};
+		let filter = tokens.filter()?;
+		Command::List { department, filter }
+	} else if verb.is_keyword("group") {
+		tokens.keyword("by")?;
+		let field = tokens.word("a field such as `location` or `title`")?;
+		let department = if tokens.optional_keyword("in") {
+			Some(tokens.word("a department")?)
+		} else {
+			None
+		};
+		let filter = tokens.filter()?;
+		Command::Group { field, department, filter }
+	} else if verb.is_keyword("show") {
+		Command::Show(tokens.word("a name")?)
+	} else if verb.is_keyword("set") {
+		let employee = tokens.word("a name")?;
+		let field = tokens.word("a field such as `title` or `start`")?;
+		tokens.keyword("to")?;
+		let value = Some(tokens.word("a value")?);
+		Command::Set { employee, field, value }
+	} else if verb.is_keyword("unset") {
+		let employee = tokens.word("a name")?;
+		let field = tokens.word("a field such as `title` or `start`")?;
+		Command::Set { employee, field, value: None }
+	} else if verb.is_keyword("roster") {
+		match tokens.next() {
+			Some(token) => Command::Roster(token.text.clone()),
+			None => Command::Roster(String::from("text")),
+		}
+	} else if verb.is_keyword("find") {
+		Command::Find(tokens.word("the start of a name")?)
+	} else if verb.is_keyword("create") {
+		Command::Create(tokens.word("a department")?)
+	} else if verb.is_keyword("rename") {
+		let department = tokens.word("a department")?;
+		tokens.keyword("to")?;
+		let to = tokens.word("the new name")?;
+		Command::Rename { department, to }
+	} else if verb.is_keyword("merge") {
+		let department = tokens.word("a department")?;
+		tokens.keyword("into")?;
+		let into = tokens.word("the department to merge into")?;
+		Command::Merge { department, into }
+	} else if verb.is_keyword("delete") {
+		let department = tokens.word("a department")?;
+		let orphans = if tokens.optional_keyword("keep") {
+			Orphans::Keep
+		} else if tokens.optional_keyword("remove") {
+			Orphans::Remove
+		} else if tokens.optional_keyword("move") {
+			tokens.keyword("to")?;
+			Orphans::MoveTo(tokens.word("a department")?)
+		} else {
+			Orphans::Refuse
+		};
+		Command::Delete { department, orphans }
+	} else if verb.is_keyword("limit") {
+		let department = tokens.word("a department")?;
+		tokens.keyword("to")?;
+		let limit = if tokens.optional_keyword("none") {
+			None
+		} else {
+			Some(tokens.limit()?)
+		};
+		Command::Limit { department, limit }
+	} else if verb.is_keyword("report") {
+		let employee = tokens.word("a name")?;
+		tokens.keyword("to")?;
+		let manager = if tokens.optional_keyword("nobody") {
+			None
+		} else {
+			Some(tokens.word("a manager or `nobody`")?)
+		};
+		Command::Report { employee, manager }
+	} else if verb.is_keyword("chain") {
+		Command::Chain(tokens.word("a name")?)
+	} else if verb.is_keyword("reports") {
+		Command::Reports(tokens.word("a name")?)
+	} else if verb.is_keyword("chart") {
+		Command::Chart
+	} else if verb.is_keyword("import") {
+		Command::Import(tokens.word("a file to import")?)
+	} else if verb.is_keyword("export") {
+		Command::Export(tokens.word("a file to export to")?)
+	} else if verb.is_keyword("undo") {
+		Command::Undo
+	} else if verb.is_keyword("redo") {
+		Command::Redo
+	} else if verb.is_keyword("history") {
+		Command::History
+	} else if verb.is_keyword("help") {
+		Command::Help
+	} else if verb.is_keyword("quit") || verb.is_keyword("exit") {
+		Command::Quit
+	} else {
+		return Err(ParseError::at(verb, format!("unknown command {}", verb.describe())));
+	};
+
+	tokens.finish()?;
+	Ok(command)
+}
+
+struct Tokens<'a> {
+	tokens: std::slice::Iter<'a, Token>,
+	// Column just past the end of the line, for "expected more" errors.
+	end: usize,
+}
+
+impl<'a> Tokens<'a> {
+	fn next(&mut self) -> Option<&'a Token> {
+		self.tokens.next()
+	}
+
+	fn missing(&self, expected: &str) -> ParseError {
+		ParseError {
+			message: format!("expected {}", expected),
+			column: self.end,
+			width: 1,
+		}
+	}
+
+	// A name or department. Keywords can't be used without quotes,
+	// which catches things like `Add to Sales`.
+	fn word(&mut self, expected: &str) -> Result<String, ParseError> {
+		match self.next() {
+			Some(token) if is_reserved(token) => Err(ParseError::at(
+				token,
+				format!("expected {}, found {} (put it in quotes if you mean the name)", expected, token.describe()),
+			)),
+			Some(token) => Ok(token.text.clone()),
+			None => Err(self.missing(expected)),
+		}
+	}
+
+	fn keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+		match self.next() {
+			Some(token) if token.is_keyword(keyword) => Ok(()),
+			Some(token) => Err(ParseError::at(
+				token,
+				format!(
+					"expected `{}`, found {} (put names with spaces in quotes)",
+					keyword,
+					token.describe()
+				),
+			)),
+			None => Err(self.missing(&format!("`{}`", keyword))),
+		}
+	}
+
+	fn optional_keyword(&mut self, keyword: &str) -> bool {
+		match self.tokens.as_slice().first() {
+			Some(token) if token.is_keyword(keyword) => {
+				self.next();
+				true
+			},
+			_ => false,
+		}
+	}
+
+	fn date(&mut self) -> Result<Date, ParseError> {
+		match self.next() {
+			Some(token) => token.text.parse().map_err(|e| ParseError::at(token, e)),
+			None => Err(self.missing("a date like 2024-03-01")),
+		}
+	}
+
+	// A headcount limit of at least one.
+	fn limit(&mut self) -> Result<usize, ParseError> {
+		match self.next() {
+			Some(token) => match token.text.parse() {
+				Ok(limit) if limit > 0 => Ok(limit),
+				_ => Err(ParseError::at(
+					token,
+					format!("expected a number of people or `none`, found {}", token.describe()),
+				)),
+			},
+			None => Err(self.missing("a number of people or `none`")),
+		}
+	}
+
+	// Whatever `hired` and `where` clauses are left on the line.
+	fn filter(&mut self) -> Result<Filter, ParseError> {
+		let mut filter = Filter::default();
+
+		loop {
+			if self.optional_keyword("hired") {
+				if self.optional_keyword("after") {
+					filter.hired_after = Some(self.date()?);
+				} else if self.optional_keyword("before") {
+					filter.hired_before = Some(self.date()?);
+				} else {
+					return Err(match self.next() {
+						Some(token) => ParseError::at(token, format!("expected `after` or `before`, found {}", token.describe())),
+						None => self.missing("`after` or `before`"),
+					});
+				}
+			} else if self.optional_keyword("where") {
+				filter.conditions.push(self.condition()?);
+				while self.optional_keyword("and") {
+					filter.conditions.push(self.condition()?);
+				}
+			} else {
+				return Ok(filter);
+			}
+		}
+	}
+
+	// `field=value`, with or without spaces around the `=`. A value
+	// with spaces goes in quotes: `location="New York"`.
+	fn condition(&mut self) -> Result<(String, String), ParseError> {
+		let token = match self.next() {
+			Some(token) => token,
+			None => return Err(self.missing("a condition like location=Berlin")),
+		};
+
+		let (field, value) = match token.text.split_once('=') {
+			Some((field, value)) if !token.quoted => (field.trim().to_string(), value.trim().to_string()),
+			_ => {
+				let field = token.text.clone();
+				match self.next() {
+					Some(equals) if !equals.quoted && equals.text.starts_with('=') => {
+						(field, equals.text[1..].trim().to_string())
+					},
+					Some(other) => {
+						return Err(ParseError::at(other, format!("expected `=`, found {}", other.describe())))
+					},
+					None => return Err(self.missing("`=` and a value")),
+				}
+			},
+		};
+
+		if field.is_empty() {
+			return Err(ParseError::at(token, String::from("the condition needs a field before the `=`")));
+		}
+
+		// The value can be the next token, as in `location= Berlin` or `location="New York"`.
+		let value = if value.is_empty() {
+			self.word("a value after the `=`")?
+		} else {
+			value
+		};
+
+		Ok((field, value))
+	}
+
+	fn finish(&mut self) -> Result<(), ParseError> {
+		match self.next() {
+			Some(token) => Err(ParseError::at(
+				token,
+				format!("unexpected {} (put names with spaces in quotes)", token.describe()),
+			)),
+			None => Ok(()),
+		}
+	}
+}
+
+fn is_reserved(token: &Token) -> bool {
+	["to", "from"].iter().any(|keyword| token.is_keyword(keyword))
+}
+
+// Splits on whitespace, keeping "quoted text" together as one token.
+fn tokenize(line: &str) -> Result<Vec<Token>, ParseError> {
+	let mut tokens = Vec::new();
+	let mut chars = line.chars().enumerate().peekable();
+
+	while let Some(&(column, c)) = chars.peek() {
+		if c.is_whitespace() {
+			chars.next();
+			continue;
+		}
+
+		let mut text = String::new();
+		let quoted = c == '"';
+
+		if quoted {
+			chars.next();
+			let mut closed = false;
+			for (_, c) in chars.by_ref() {
+				if c == '"' {
+					closed = true;
+					break;
+				}
+				text.push(c);
+			}
+			if !closed {
+				return Err(ParseError {
+					message: String::from("this quote is never closed"),
+					column,
+					width: 1,
+				});
+			}
+			if text.trim().is_empty() {
+				return Err(ParseError {
+					message: String::from("empty quotes"),
+					column,
+					width: 2,
+				});
+			}
+		} else {
+			while let Some(&(_, c)) = chars.peek() {
+				if c.is_whitespace() || c == '"' {
+					break;
+				}
+				text.push(c);
+				chars.next();
+			}
+		}
+
+		let width = match chars.peek() {
+			Some(&(next, _)) => next - column,
+			None => line.chars().count() - column,
+		};
+
+		tokens.push(Token {
+			text: text.trim().to_string(),
+			quoted,
+			column,
+			width,
+		});
+	}
+
+	Ok(tokens)
+}
@@ -0,0 +1,947 @@
+// company:
+
+// The employee directory behind the text interface.
+// Every employee gets a numeric id when they're added, so two people
+// called Sally are two different employees, and an employee can belong
+// to any number of departments. Each employee can also report to a
+// manager, which makes the company an org chart, and carries a few
+// details of their own: a title, a start date, an email address, a
+// location, and any other named attributes a team wants to keep.
+// Departments exist in their own right: they can be created before
+// anyone is in them, renamed, merged and deleted, and can have a cap on
+// how many people they hold. Adding someone to a department that doesn't
+// exist yet still creates it on the way.
+// Everything the company does to itself is also noted down as a list
+// of `Change`s, which is what the history log keeps and undo reverses.
+// ====================================================================
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::date::Date;
+use super::search;
+
+pub type EmployeeId = u32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Employee {
+	pub id: EmployeeId,
+	pub name: String,
+	pub departments: BTreeSet<String>,
+	#[serde(default)]
+	pub manager: Option<EmployeeId>,
+	#[serde(flatten)]
+	pub details: Details,
+}
+
+// What there is to know about an employee besides who they are and
+// where they sit in the company. Kept apart so that files and requests
+// that describe a new employee can use the same names.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Details {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub title: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub start_date: Option<Date>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub email: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub location: Option<String>,
+	// Anything else worth knowing, such as `team` or `desk`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty", deserialize_with = "attribute_names")]
+	pub attributes: BTreeMap<String, String>,
+}
+
+// Attributes from a file or a request are looked up by `field_name` like
+// any other field, so `Team` is kept as `team`.
+fn attribute_names<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error> {
+	let attributes = BTreeMap::<String, String>::deserialize(deserializer)?;
+	Ok(attributes.into_iter().map(|(name, value)| (field_name(&name), value)).collect())
+}
+
+impl Details {
+	// One detail by field name, written out as text.
+	pub fn field(&self, field: &str) -> Option<String> {
+		match field_name(field).as_str() {
+			"title" => self.title.clone(),
+			"start" => self.start_date.map(|date| date.to_string()),
+			"email" => self.email.clone(),
+			"location" => self.location.clone(),
+			other => self.attributes.get(other).cloned(),
+		}
+	}
+
+	// Every detail that has been filled in, by field name: the standard
+	// ones first, then the attributes in alphabetical order.
+	pub fn fields(&self) -> Vec<(String, String)> {
+		FIELDS
+			.iter()
+			.filter_map(|field| self.field(field).map(|value| (field.to_string(), value)))
+			.chain(self.attributes.iter().map(|(field, value)| (field.clone(), value.clone())))
+			.collect()
+	}
+}
+
+// The details `Employee::field` and `Company::set_field` know by name.
+// Any other name is one of the employee's own attributes.
+pub const FIELDS: [&str; 4] = ["title", "start", "email", "location"];
+
+impl Employee {
+	fn new(id: EmployeeId, name: String) -> Employee {
+		Employee {
+			id,
+			name,
+			departments: BTreeSet::new(),
+			manager: None,
+			details: Details::default(),
+		}
+	}
+
+	// One of the employee's details by name, or their name itself.
+	pub fn field(&self, field: &str) -> Option<String> {
+		match field_name(field).as_str() {
+			"name" => Some(self.name.clone()),
+			other => self.details.field(other),
+		}
+	}
+}
+
+// Field names are case-insensitive, and a few have other spellings.
+pub fn field_name(field: &str) -> String {
+	let field = field.trim().to_lowercase();
+	match field.as_str() {
+		"start_date" | "start date" | "started" | "hired" => String::from("start"),
+		"e-mail" | "mail" => String::from("email"),
+		_ => field,
+	}
+}
+
+impl fmt::Display for Employee {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} (#{})", self.name, self.id)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompanyError {
+	EmptyName,
+	EmptyDepartment,
+	UnknownEmployee(EmployeeId),
+	// No employee has this name. The second part is a close name, if any.
+	NoSuchName(String, Option<String>),
+	// More than one employee has this name; the ids tell them apart.
+	AmbiguousName(String, Vec<EmployeeId>),
+	NotInDepartment(EmployeeId, String),
+	AlreadyInDepartment(EmployeeId, String),
+	// A move has to say which department it's from.
+	SeveralDepartments(EmployeeId),
+	// Making the first employee report to the second would put someone
+	// above themselves.
+	ManagerCycle(EmployeeId, EmployeeId),
+	// A change tried to bring back an id that is already in use.
+	DuplicateId(EmployeeId),
+	// Names, departments and managers have commands of their own.
+	ReservedField(String),
+	// The field, and what is wrong with the value.
+	InvalidValue(String, String),
+	// No department has this name. The second part is a close name, if any.
+	UnknownDepartment(String, Option<String>),
+	DepartmentExists(String),
+	// Merging a department into itself, or moving its members there.
+	SameDepartment(String),
+	// The department and its headcount limit.
+	DepartmentFull(String, usize),
+	// The department, the limit asked for, and how many are in it already.
+	LimitTooLow(String, usize, usize),
+	// Deleting the department would leave this many people in none at all.
+	Orphans(String, usize),
+}
+
+impl fmt::Display for CompanyError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			CompanyError::EmptyName => write!(f, "the name can't be empty"),
+			CompanyError::EmptyDepartment => write!(f, "the department can't be empty"),
+			CompanyError::UnknownEmployee(id) => write!(f, "there is no employee #{}", id),
+			CompanyError::NoSuchName(name, None) => write!(f, "there is no employee called {}", name),
+			CompanyError::NoSuchName(name, Some(close)) => {
+				write!(f, "there is no employee called {} (did you mean {}?)", name, close)
+			},
+			CompanyError::AmbiguousName(name, ids) => {
+				let ids: Vec<String> = ids.iter().map(|id| format!("#{}", id)).collect();
+				write!(f, "there are several employees called {}, use one of {}", name, ids.join(", "))
+			},
+			CompanyError::NotInDepartment(id, department) => {
+				write!(f, "employee #{} is not in {}", id, department)
+			},
+			CompanyError::AlreadyInDepartment(id, department) => {
+				write!(f, "employee #{} is already in {}", id, department)
+			},
+			CompanyError::SeveralDepartments(id) => {
+				write!(f, "employee #{} is in several departments, say which one with `from`", id)
+			},
+			CompanyError::ManagerCycle(id, manager) if id == manager => {
+				write!(f, "#{} can't report to themselves", id)
+			},
+			CompanyError::ManagerCycle(id, manager) => {
+				write!(f, "#{} can't report to #{}, who already reports to #{}", id, manager, id)
+			},
+			CompanyError::DuplicateId(id) => write!(f, "there is already an employee #{}", id),
+			CompanyError::ReservedField(field) => {
+				write!(f, "{} can't be set directly, use Add, Move or Report instead", field)
+			},
+			CompanyError::InvalidValue(field, message) => write!(f, "bad {}: {}", field, message),
+			CompanyError::UnknownDepartment(name, None) => write!(f, "there is no department called {}", name),
+			CompanyError::UnknownDepartment(name, Some(close)) => {
+				write!(f, "there is no department called {} (did you mean {}?)", name, close)
+			},
+			CompanyError::DepartmentExists(name) => write!(f, "there is already a department called {}", name),
+			CompanyError::SameDepartment(name) => write!(f, "{} can't be merged into itself", name),
+			CompanyError::DepartmentFull(name, limit) => {
+				write!(f, "{} is full, it has a limit of {} {}", name, limit, people(*limit))
+			},
+			CompanyError::LimitTooLow(name, limit, count) => {
+				write!(f, "{} already has {} {}, more than a limit of {}", name, count, people(*count), limit)
+			},
+			CompanyError::Orphans(name, count) => write!(
+				f,
+				"{} {} in {} would be left in no department, say whether to keep, remove or move them to another",
+				count,
+				people(*count),
+				name
+			),
+		}
+	}
+}
+
+impl std::error::Error for CompanyError {}
+
+pub fn people(count: usize) -> &'static str {
+	if count == 1 {
+		"person"
+	} else {
+		"people"
+	}
+}
+
+// What happens to the people in a deleted department who aren't in any
+// other department.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Orphans {
+	// Don't delete the department if there are any.
+	Refuse,
+	// They stay in the company without a department.
+	Keep,
+	// They leave the company.
+	Remove,
+	// They join this department instead.
+	MoveTo(String),
+}
+
+// The smallest steps the directory changes by. Removing an employee, for
+// example, is them leaving each of their departments, their reports
+// getting a new manager, and only then the removal itself, so that
+// every step can be undone on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Change {
+	Hired { id: EmployeeId, name: String },
+	Joined { id: EmployeeId, department: String },
+	Left { id: EmployeeId, department: String },
+	ManagerChanged { id: EmployeeId, from: Option<EmployeeId>, to: Option<EmployeeId> },
+	// A detail such as the title or an attribute, by its field name.
+	Updated { id: EmployeeId, field: String, from: Option<String>, to: Option<String> },
+	Removed { id: EmployeeId, name: String },
+	// Only ever recorded for a department with nobody in it.
+	DepartmentCreated { department: String },
+	DepartmentDeleted { department: String },
+	DepartmentRenamed { from: String, to: String },
+	LimitChanged { department: String, from: Option<usize>, to: Option<usize> },
+}
+
+impl Change {
+	// The change that puts things back the way they were.
+	pub fn inverse(&self) -> Change {
+		match self.clone() {
+			Change::Hired { id, name } => Change::Removed { id, name },
+			Change::Removed { id, name } => Change::Hired { id, name },
+			Change::Joined { id, department } => Change::Left { id, department },
+			Change::Left { id, department } => Change::Joined { id, department },
+			Change::ManagerChanged { id, from, to } => Change::ManagerChanged { id, from: to, to: from },
+			Change::Updated { id, field, from, to } => Change::Updated {
+				id,
+				field,
+				from: to,
+				to: from,
+			},
+			Change::DepartmentCreated { department } => Change::DepartmentDeleted { department },
+			Change::DepartmentDeleted { department } => Change::DepartmentCreated { department },
+			Change::DepartmentRenamed { from, to } => Change::DepartmentRenamed { from: to, to: from },
+			Change::LimitChanged { department, from, to } => Change::LimitChanged {
+				department,
+				from: to,
+				to: from,
+			},
+		}
+	}
+}
+
+impl fmt::Display for Change {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let manager = |manager: &Option<EmployeeId>| match manager {
+			Some(manager) => format!("#{}", manager),
+			None => String::from("nobody"),
+		};
+
+		match self {
+			Change::Hired { id, name } => write!(f, "{} (#{}) joined the company", name, id),
+			Change::Joined { id, department } => write!(f, "#{} joined {}", id, department),
+			Change::Left { id, department } => write!(f, "#{} left {}", id, department),
+			Change::ManagerChanged { id, from, to } => {
+				write!(f, "#{} reports to {} instead of {}", id, manager(to), manager(from))
+			},
+			Change::Updated { id, field, to: Some(to), .. } => write!(f, "#{} {} set to {}", id, field, to),
+			Change::Updated { id, field, to: None, .. } => write!(f, "#{} {} cleared", id, field),
+			Change::Removed { id, name } => write!(f, "{} (#{}) left the company", name, id),
+			Change::DepartmentCreated { department } => write!(f, "{} was created", department),
+			Change::DepartmentDeleted { department } => write!(f, "{} was deleted", department),
+			Change::DepartmentRenamed { from, to } => write!(f, "{} was renamed to {}", from, to),
+			Change::LimitChanged { department, to: Some(to), .. } => {
+				write!(f, "{} can have at most {} {}", department, to, people(*to))
+			},
+			Change::LimitChanged { department, to: None, .. } => write!(f, "{} has no limit", department),
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+struct Department {
+	// Kept sorted by name and then id.
+	members: BTreeSet<(String, EmployeeId)>,
+	// The most people the department can hold, if there's a cap.
+	limit: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Company {
+	next_id: EmployeeId,
+	employees: HashMap<EmployeeId, Employee>,
+	departments: BTreeMap<String, Department>,
+	// What has changed since `take_changes` was last called.
+	changes: Vec<Change>,
+}
+
+impl Company {
+	pub fn new() -> Company {
+		Company {
+			next_id: 1,
+			..Company::default()
+		}
+	}
+
+	// Rebuilds a company from saved departments, with their limits, and
+	// employees. `next_id` is kept so ids of removed employees are never
+	// handed out again. The changes it took to get here are left for
+	// `take_changes`.
+	pub fn restore(
+		next_id: EmployeeId,
+		departments: Vec<(String, Option<usize>)>,
+		employees: Vec<Employee>,
+	) -> Result<Company, CompanyError> {
+		let mut company = Company::new();
+
+		for (department, _) in &departments {
+			company.create_department(department)?;
+		}
+
+		let mut managers = Vec::new();
+
+		for employee in employees {
+			let id = employee.id;
+			if let Some(manager) = employee.manager {
+				managers.push((id, manager));
+			}
+			company.insert_employee(id, employee.name.clone())?;
+
+			for department in &employee.departments {
+				company.assign(id, department)?;
+			}
+			company.set_details(id, &employee.details)?;
+		}
+
+		// Managers go in once everyone exists, so a bad file can't sneak in
+		// a loop or a manager who isn't there.
+		for (id, manager) in managers {
+			company.set_manager(id, Some(manager))?;
+		}
+
+		// Limits go in last too, so they're checked against everyone at once.
+		for (department, limit) in departments {
+			company.set_limit(&department, limit)?;
+		}
+
+		company.next_id = company.next_id.max(next_id);
+		Ok(company)
+	}
+
+	// Everything that changed since the last call, oldest first.
+	pub fn take_changes(&mut self) -> Vec<Change> {
+		std::mem::take(&mut self.changes)
+	}
+
+	// How many changes are waiting to be taken, to roll back to later.
+	pub fn pending(&self) -> usize {
+		self.changes.len()
+	}
+
+	// Takes back every change made since `pending` returned `mark`, for
+	// something that failed part way through.
+	pub fn roll_back(&mut self, mark: usize) {
+		let changes = self.changes.split_off(mark);
+		self.take_back(&changes);
+	}
+
+	// Plays `changes`, which were the last ones made, backwards, without
+	// recording that as changes of its own.
+	pub fn take_back(&mut self, changes: &[Change]) {
+		let mark = self.changes.len();
+		for change in changes.iter().rev() {
+			self.apply(&change.inverse()).expect("a change that was just made can be taken back");
+		}
+		self.changes.truncate(mark);
+	}
+
+	// Makes one change, as if it had been done through the other methods.
+	pub fn apply(&mut self, change: &Change) -> Result<(), CompanyError> {
+		match change {
+			Change::Hired { id, name } => self.insert_employee(*id, name.clone()),
+			Change::Joined { id, department } => self.assign(*id, department),
+			Change::Left { id, department } => self.unassign(*id, department),
+			Change::ManagerChanged { id, to, .. } => self.set_manager(*id, *to),
+			Change::Updated { id, field, to, .. } => self.set_field(*id, field, to.as_deref()),
+			Change::Removed { id, .. } => self.remove(*id).map(|_| ()),
+			Change::DepartmentCreated { department } => self.create_department(department),
+			// Everyone has left by the time the department goes.
+			Change::DepartmentDeleted { department } => {
+				self.delete_department(department, &Orphans::Refuse).map(|_| ())
+			},
+			Change::DepartmentRenamed { from, to } => self.rename_department(from, to),
+			Change::LimitChanged { department, to, .. } => self.set_limit(department, *to),
+		}
+	}
+
+	// The id the next new employee will get.
+	pub fn next_id(&self) -> EmployeeId {
+		self.next_id
+	}
+
+	// Adds a new employee, even if someone with the same name already exists.
+	pub fn add(&mut self, name: &str, department: &str) -> Result<EmployeeId, CompanyError> {
+		let department = non_empty(department, CompanyError::EmptyDepartment)?;
+		self.check_room(&department, 1)?;
+		let id = self.hire(name)?;
+		self.assign(id, &department)?;
+
+		Ok(id)
+	}
+
+	// Adds a new employee who isn't in any department yet.
+	pub fn hire(&mut self, name: &str) -> Result<EmployeeId, CompanyError> {
+		let name = non_empty(name, CompanyError::EmptyName)?;
+
+		let id = self.next_id;
+		self.insert_employee(id, name)?;
+
+		Ok(id)
+	}
+
+	fn insert_employee(&mut self, id: EmployeeId, name: String) -> Result<(), CompanyError> {
+		non_empty(&name, CompanyError::EmptyName)?;
+		if self.employees.contains_key(&id) {
+			return Err(CompanyError::DuplicateId(id));
+		}
+
+		self.next_id = self.next_id.max(id + 1);
+		self.employees.insert(id, Employee::new(id, name.clone()));
+		self.changes.push(Change::Hired { id, name });
+
+		Ok(())
+	}
+
+	// Puts an existing employee in another department as well, creating
+	// the department if it doesn't exist yet.
+	pub fn assign(&mut self, id: EmployeeId, department: &str) -> Result<(), CompanyError> {
+		let department = non_empty(department, CompanyError::EmptyDepartment)?;
+		let employee = self.employees.get(&id).ok_or(CompanyError::UnknownEmployee(id))?;
+
+		if employee.departments.contains(&department) {
+			return Err(CompanyError::AlreadyInDepartment(id, department));
+		}
+		self.check_room(&department, 1)?;
+		if !self.departments.contains_key(&department) {
+			self.create_department(&department)?;
+		}
+
+		let employee = self.employees.get_mut(&id).unwrap();
+		employee.departments.insert(department.clone());
+		if let Some(members) = self.departments.get_mut(&department) {
+			members.members.insert((employee.name.clone(), id));
+		}
+		self.changes.push(Change::Joined { id, department });
+
+		Ok(())
+	}
+
+	// Takes an employee out of one department. They stay in the company,
+	// even if that was their last department, and the department stays
+	// even if they were the last one in it.
+	pub fn unassign(&mut self, id: EmployeeId, department: &str) -> Result<(), CompanyError> {
+		let employee = self.employees.get_mut(&id).ok_or(CompanyError::UnknownEmployee(id))?;
+
+		if !employee.departments.remove(department) {
+			return Err(CompanyError::NotInDepartment(id, department.to_string()));
+		}
+
+		let key = (employee.name.clone(), id);
+		if let Some(members) = self.departments.get_mut(department) {
+			members.members.remove(&key);
+		}
+		self.changes.push(Change::Left {
+			id,
+			department: department.to_string(),
+		});
+
+		Ok(())
+	}
+
+	// Removes an employee from the company and all of their departments.
+	// Anyone who reported to them now reports to their manager instead.
+	// Their details are cleared one by one first, so undoing the removal
+	// brings those back too.
+	pub fn remove(&mut self, id: EmployeeId) -> Result<Employee, CompanyError> {
+		let (departments, manager, details) = match self.employees.get(&id) {
+			Some(employee) => (employee.departments.clone(), employee.manager, employee.details.clone()),
+			None => return Err(CompanyError::UnknownEmployee(id)),
+		};
+
+		for (field, _) in details.fields() {
+			self.set_field(id, &field, None)?;
+		}
+
+		for department in &departments {
+			self.unassign(id, department)?;
+		}
+
+		let reports: Vec<EmployeeId> = self.direct_reports(id).iter().map(|report| report.id).collect();
+		for report in reports {
+			self.set_manager(report, manager)?;
+		}
+		self.set_manager(id, None)?;
+
+		let mut employee = self.employees.remove(&id).ok_or(CompanyError::UnknownEmployee(id))?;
+		self.changes.push(Change::Removed {
+			id,
+			name: employee.name.clone(),
+		});
+
+		employee.departments = departments;
+		employee.manager = manager;
+		employee.details = details;
+		Ok(employee)
+	}
+
+	// Moves an employee from one department to another.
+	pub fn transfer(&mut self, id: EmployeeId, from: &str, to: &str) -> Result<(), CompanyError> {
+		let to = non_empty(to, CompanyError::EmptyDepartment)?;
+		let employee = self.employees.get(&id).ok_or(CompanyError::UnknownEmployee(id))?;
+
+		if !employee.departments.contains(from) {
+			return Err(CompanyError::NotInDepartment(id, from.to_string()));
+		}
+		if employee.departments.contains(&to) {
+			return Err(CompanyError::AlreadyInDepartment(id, to));
+		}
+		self.check_room(&to, 1)?;
+
+		self.unassign(id, from)?;
+		self.assign(id, &to)
+	}
+
+	// Makes a department with nobody in it yet.
+	pub fn create_department(&mut self, name: &str) -> Result<(), CompanyError> {
+		let name = non_empty(name, CompanyError::EmptyDepartment)?;
+		if self.departments.contains_key(&name) {
+			return Err(CompanyError::DepartmentExists(name));
+		}
+
+		self.departments.insert(name.clone(), Department::default());
+		self.changes.push(Change::DepartmentCreated { department: name });
+		Ok(())
+	}
+
+	// Gives a department a new name, keeping its members and limit.
+	pub fn rename_department(&mut self, from: &str, to: &str) -> Result<(), CompanyError> {
+		let to = non_empty(to, CompanyError::EmptyDepartment)?;
+		self.department(from)?;
+		if self.departments.contains_key(&to) {
+			return Err(CompanyError::DepartmentExists(to));
+		}
+
+		let department = self.departments.remove(from).unwrap();
+		for (_, id) in &department.members {
+			if let Some(employee) = self.employees.get_mut(id) {
+				employee.departments.remove(from);
+				employee.departments.insert(to.clone());
+			}
+		}
+		self.departments.insert(to.clone(), department);
+		self.changes.push(Change::DepartmentRenamed {
+			from: from.to_string(),
+			to,
+		});
+
+		Ok(())
+	}
+
+	// Moves everyone in `from` into `into` and deletes `from`. Returns how
+	// many people joined `into`; anyone already in both just leaves `from`.
+	pub fn merge_departments(&mut self, from: &str, into: &str) -> Result<usize, CompanyError> {
+		let members = self.member_ids(from)?;
+		self.department(into)?;
+		if from == into {
+			return Err(CompanyError::SameDepartment(from.to_string()));
+		}
+
+		let joining: Vec<EmployeeId> = members
+			.iter()
+			.copied()
+			.filter(|id| !self.employees[id].departments.contains(into))
+			.collect();
+		self.check_room(into, joining.len())?;
+
+		for &id in &members {
+			self.unassign(id, from)?;
+		}
+		for &id in &joining {
+			self.assign(id, into)?;
+		}
+		self.delete_department(from, &Orphans::Refuse)?;
+
+		Ok(joining.len())
+	}
+
+	// Deletes a department. Its members leave it, and `orphans` says what
+	// happens to those of them who aren't in any other department.
+	// Returns who those were.
+	pub fn delete_department(&mut self, name: &str, orphans: &Orphans) -> Result<Vec<EmployeeId>, CompanyError> {
+		let members = self.member_ids(name)?;
+		let orphaned: Vec<EmployeeId> = members
+			.iter()
+			.copied()
+			.filter(|id| self.employees[id].departments.len() == 1)
+			.collect();
+
+		match orphans {
+			Orphans::Refuse if !orphaned.is_empty() => {
+				return Err(CompanyError::Orphans(name.to_string(), orphaned.len()));
+			},
+			Orphans::MoveTo(to) if to == name => return Err(CompanyError::SameDepartment(name.to_string())),
+			Orphans::MoveTo(to) => {
+				self.department(to)?;
+				self.check_room(to, orphaned.len())?;
+			},
+			_ => {},
+		}
+
+		for &id in &members {
+			if *orphans == Orphans::Remove && orphaned.contains(&id) {
+				self.remove(id)?;
+			} else {
+				self.unassign(id, name)?;
+			}
+		}
+		if let Orphans::MoveTo(to) = orphans {
+			for &id in &orphaned {
+				self.assign(id, to)?;
+			}
+		}
+
+		// Taking the limit off first means undo puts it back.
+		self.set_limit(name, None)?;
+		self.departments.remove(name);
+		self.changes.push(Change::DepartmentDeleted {
+			department: name.to_string(),
+		});
+
+		Ok(orphaned)
+	}
+
+	// Caps how many people a department can hold, or lifts the cap with `None`.
+	pub fn set_limit(&mut self, name: &str, limit: Option<usize>) -> Result<(), CompanyError> {
+		let count = self.department(name)?.members.len();
+		if let Some(limit) = limit.filter(|&limit| count > limit) {
+			return Err(CompanyError::LimitTooLow(name.to_string(), limit, count));
+		}
+
+		let department = self.departments.get_mut(name).unwrap();
+		if department.limit != limit {
+			self.changes.push(Change::LimitChanged {
+				department: name.to_string(),
+				from: department.limit,
+				to: limit,
+			});
+			department.limit = limit;
+		}
+		Ok(())
+	}
+
+	fn department(&self, name: &str) -> Result<&Department, CompanyError> {
+		self.departments.get(name).ok_or_else(|| {
+			let close = search::suggest(name, self.department_names()).map(String::from);
+			CompanyError::UnknownDepartment(name.to_string(), close)
+		})
+	}
+
+	fn member_ids(&self, name: &str) -> Result<Vec<EmployeeId>, CompanyError> {
+		Ok(self.department(name)?.members.iter().map(|(_, id)| *id).collect())
+	}
+
+	// Whether `count` more people fit in a department. One that doesn't
+	// exist yet has room for anyone.
+	fn check_room(&self, name: &str, count: usize) -> Result<(), CompanyError> {
+		match self.departments.get(name) {
+			Some(Department { members, limit: Some(limit) }) if members.len() + count > *limit => {
+				Err(CompanyError::DepartmentFull(name.to_string(), *limit))
+			},
+			_ => Ok(()),
+		}
+	}
+
+	// Makes `id` report to `manager`, or to nobody.
+	pub fn set_manager(&mut self, id: EmployeeId, manager: Option<EmployeeId>) -> Result<(), CompanyError> {
+		if !self.employees.contains_key(&id) {
+			return Err(CompanyError::UnknownEmployee(id));
+		}
+
+		if let Some(manager) = manager {
+			if !self.employees.contains_key(&manager) {
+				return Err(CompanyError::UnknownEmployee(manager));
+			}
+			// Walking up from the new manager must never reach `id` itself.
+			if manager == id || self.chain(manager).iter().any(|boss| boss.id == id) {
+				return Err(CompanyError::ManagerCycle(id, manager));
+			}
+		}
+
+		let employee = self.employees.get_mut(&id).unwrap();
+		if employee.manager != manager {
+			self.changes.push(Change::ManagerChanged {
+				id,
+				from: employee.manager,
+				to: manager,
+			});
+			employee.manager = manager;
+		}
+		Ok(())
+	}
+
+	// Sets one of an employee's details by name, or clears it with `None`.
+	pub fn set_field(&mut self, id: EmployeeId, field: &str, value: Option<&str>) -> Result<(), CompanyError> {
+		let field = field_name(field);
+		if field.is_empty() {
+			return Err(CompanyError::InvalidValue(field, String::from("the field needs a name")));
+		}
+		if ["name", "id", "department", "departments", "manager"].contains(&field.as_str()) {
+			return Err(CompanyError::ReservedField(field));
+		}
+
+		let value = value.map(str::trim).filter(|value| !value.is_empty());
+		let employee = self.employees.get_mut(&id).ok_or(CompanyError::UnknownEmployee(id))?;
+		let from = employee.field(&field);
+
+		let details = &mut employee.details;
+		match field.as_str() {
+			"title" => details.title = value.map(String::from),
+			"start" => {
+				details.start_date = match value {
+					Some(value) => Some(value.parse().map_err(|e| CompanyError::InvalidValue(field.clone(), e))?),
+					None => None,
+				}
+			},
+			"email" => {
+				if let Some(value) = value.filter(|value| !value.contains('@') || value.contains(char::is_whitespace)) {
+					return Err(CompanyError::InvalidValue(
+						field,
+						format!("'{}' doesn't look like an email address", value),
+					));
+				}
+				details.email = value.map(String::from);
+			},
+			"location" => details.location = value.map(String::from),
+			_ => match value {
+				Some(value) => {
+					details.attributes.insert(field.clone(), value.to_string());
+				},
+				None => {
+					details.attributes.remove(&field);
+				},
+			},
+		}
+
+		let to = employee.field(&field);
+		if from != to {
+			self.changes.push(Change::Updated { id, field, from, to });
+		}
+		Ok(())
+	}
+
+	// Fills in every detail that `details` has, one field at a time.
+	pub fn set_details(&mut self, id: EmployeeId, details: &Details) -> Result<(), CompanyError> {
+		for (field, value) in details.fields() {
+			self.set_field(id, &field, Some(&value))?;
+		}
+		Ok(())
+	}
+
+	// The employee's manager, their manager's manager, and so on up.
+	pub fn chain(&self, id: EmployeeId) -> Vec<&Employee> {
+		let mut chain = Vec::new();
+		let mut next = self.employees.get(&id).and_then(|employee| employee.manager);
+
+		while let Some(manager) = next.and_then(|manager| self.employees.get(&manager)) {
+			chain.push(manager);
+			next = manager.manager;
+		}
+
+		chain
+	}
+
+	// The people who report straight to this employee, sorted by name.
+	pub fn direct_reports(&self, id: EmployeeId) -> Vec<&Employee> {
+		let mut reports: Vec<&Employee> = self.employees
+			.values()
+			.filter(|employee| employee.manager == Some(id))
+			.collect();
+		reports.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+		reports
+	}
+
+	// Everyone below this employee, each one followed by their own reports.
+	pub fn all_reports(&self, id: EmployeeId) -> Vec<&Employee> {
+		self.org_chart_below(id, 0).into_iter().map(|(_, employee)| employee).collect()
+	}
+
+	// The whole company as a tree: everyone without a manager at depth 0,
+	// with their reports under them one level deeper.
+	pub fn org_chart(&self) -> Vec<(usize, &Employee)> {
+		let mut roots: Vec<&Employee> = self.employees
+			.values()
+			.filter(|employee| employee.manager.is_none())
+			.collect();
+		roots.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+
+		let mut chart = Vec::new();
+		for root in roots {
+			chart.push((0, root));
+			chart.extend(self.org_chart_below(root.id, 1));
+		}
+		chart
+	}
+
+	fn org_chart_below(&self, id: EmployeeId, depth: usize) -> Vec<(usize, &Employee)> {
+		let mut chart = Vec::new();
+		for report in self.direct_reports(id) {
+			chart.push((depth, report));
+			chart.extend(self.org_chart_below(report.id, depth + 1));
+		}
+		chart
+	}
+
+	pub fn get(&self, id: EmployeeId) -> Option<&Employee> {
+		self.employees.get(&id)
+	}
+
+	// Every employee, in the order they were added.
+	pub fn employees(&self) -> Vec<&Employee> {
+		let mut employees: Vec<&Employee> = self.employees.values().collect();
+		employees.sort_by_key(|employee| employee.id);
+		employees
+	}
+
+	// Finds an employee from what a user typed: either `#id` or a name.
+	// A name only works if exactly one employee has it.
+	pub fn lookup(&self, reference: &str) -> Result<EmployeeId, CompanyError> {
+		let reference = reference.trim();
+
+		if let Some(id) = reference.strip_prefix('#').and_then(|id| id.parse().ok()) {
+			return self.get(id).map(|employee| employee.id).ok_or(CompanyError::UnknownEmployee(id));
+		}
+
+		let mut ids: Vec<EmployeeId> = self.employees
+			.values()
+			.filter(|employee| employee.name == reference)
+			.map(|employee| employee.id)
+			.collect();
+		ids.sort_unstable();
+
+		match ids.len() {
+			0 => {
+				let names = self.employees.values().map(|employee| employee.name.as_str());
+				let close = search::suggest(reference, names).map(String::from);
+				Err(CompanyError::NoSuchName(reference.to_string(), close))
+			},
+			1 => Ok(ids[0]),
+			_ => Err(CompanyError::AmbiguousName(reference.to_string(), ids)),
+		}
+	}
+
+	// Every department, in alphabetical order.
+	pub fn department_names(&self) -> Vec<&str> {
+		self.departments.keys().map(|department| department.as_str()).collect()
+	}
+
+	pub fn has_department(&self, department: &str) -> bool {
+		self.departments.contains_key(department)
+	}
+
+	// The department's headcount limit, if it has one.
+	pub fn department_limit(&self, department: &str) -> Option<usize> {
+		self.departments.get(department).and_then(|department| department.limit)
+	}
+
+	// Members of a department, sorted by name. Empty for an unknown department.
+	pub fn list_department(&self, department: &str) -> Vec<&Employee> {
+		match self.departments.get(department) {
+			Some(department) => department.members.iter().map(|(_, id)| &self.employees[id]).collect(),
+			None => Vec::new(),
+		}
+	}
+
+	// Every department in alphabetical order with its sorted members.
+	pub fn list_all_by_department(&self) -> Vec<(&str, Vec<&Employee>)> {
+		self.departments
+			.keys()
+			.map(|department| (department.as_str(), self.list_department(department)))
+			.collect()
+	}
+
+	// Employees who don't belong to any department, sorted by name.
+	pub fn unassigned(&self) -> Vec<&Employee> {
+		let mut employees: Vec<&Employee> = self.employees
+			.values()
+			.filter(|employee| employee.departments.is_empty())
+			.collect();
+		employees.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+		employees
+	}
+}
+
+fn non_empty(value: &str, error: CompanyError) -> Result<String, CompanyError> {
+	let value = value.trim();
+	if value.is_empty() {
+		return Err(error);
+	}
+	Ok(value.to_string())
+}
@@ -0,0 +1,122 @@
+// date:
+
+// Calendar dates in UTC, worked out from the system clock without
+// pulling in a date library. Dates are written and read as YYYY-MM-DD,
+// in files as well as on the command line.
+// ====================================================================
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+	pub year: i32,
+	pub month: u32,
+	pub day: u32,
+}
+
+impl Date {
+	// Converts a count of days since 1970-01-01 into a year, month and day.
+	// Every 400 years of the calendar have the same number of days, so it
+	// counts whole 400-year cycles first and works within the last one.
+	pub fn from_days_since_epoch(days: i64) -> Date {
+		let days = days + 719_468;
+		let era = days.div_euclid(146_097);
+		let day_of_era = days.rem_euclid(146_097);
+		let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+		let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+		let month_index = (5 * day_of_year + 2) / 153;
+		let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+		let month = if month_index < 10 {
+			month_index + 3
+		} else {
+			month_index - 9
+		} as u32;
+		let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+
+		Date { year, month, day }
+	}
+}
+
+fn is_leap_year(year: i32) -> bool {
+	year.rem_euclid(4) == 0 && (year.rem_euclid(100) != 0 || year.rem_euclid(400) == 0)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+	match month {
+		2 if is_leap_year(year) => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+// Parses `2024-03-01`, and turns down dates like `2023-02-29`.
+impl FromStr for Date {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Date, String> {
+		let invalid = || format!("'{}' is not a date like 2024-03-01", text);
+
+		let parts: Vec<&str> = text.trim().split('-').collect();
+		if parts.len() != 3 || parts[0].len() != 4 {
+			return Err(invalid());
+		}
+
+		let year: i32 = parts[0].parse().map_err(|_| invalid())?;
+		let month: u32 = parts[1].parse().map_err(|_| invalid())?;
+		let day: u32 = parts[2].parse().map_err(|_| invalid())?;
+
+		if !(1..=12).contains(&month) {
+			return Err(format!("'{}' has no month {}", text, month));
+		}
+		if day == 0 || day > days_in_month(year, month) {
+			return Err(format!(
+				"'{}' is not a real date, {}-{:02} has {} days",
+				text,
+				year,
+				month,
+				days_in_month(year, month)
+			));
+		}
+
+		Ok(Date { year, month, day })
+	}
+}
+
+impl Serialize for Date {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for Date {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+		let text = String::deserialize(deserializer)?;
+		text.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+impl fmt::Display for Date {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+	}
+}
+
+// Seconds since 1970-01-01 UTC.
+pub fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_secs())
+		.unwrap_or(0)
+}
+
+// `2024-05-01 09:30:00` for a count of seconds from `now`.
+pub fn format_timestamp(seconds: u64) -> String {
+	let date = Date::from_days_since_epoch((seconds / 86_400) as i64);
+	let time = seconds % 86_400;
+	format!("{} {:02}:{:02}:{:02}", date, time / 3600, time / 60 % 60, time % 60)
+}
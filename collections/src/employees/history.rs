@@ -0,0 +1,213 @@
+// history:
+
+// Every change to the directory is kept as an `Event` in an append-only
+// log, so the directory can always be rebuilt by playing the log from
+// the top. Undo and redo are events too: undo plays a change backwards
+// and redo plays it forwards again, and both are added to the end of
+// the log rather than rewriting it.
+// Where the log is kept is up to the `DirectoryStore` the journal is
+// opened with.
+// ====================================================================
+
+use serde::{Deserialize, Serialize};
+
+use super::company::{Change, Company};
+use super::date;
+use super::storage::StoreError;
+use super::store::DirectoryStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+	Change,
+	Undo,
+	Redo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+	// Seconds since 1970-01-01 UTC.
+	pub at: u64,
+	pub kind: EventKind,
+	// What was asked for, such as the command that was typed. Undo and
+	// redo repeat the action they undid or redid.
+	pub action: String,
+	pub changes: Vec<Change>,
+}
+
+impl Event {
+	pub fn new(kind: EventKind, action: String, changes: Vec<Change>) -> Event {
+		Event {
+			at: date::now(),
+			kind,
+			action,
+			changes,
+		}
+	}
+}
+
+pub struct Journal {
+	store: Box<dyn DirectoryStore>,
+	events: Vec<Event>,
+	// Positions in `events` of the changes that undo and redo would pick next.
+	undo: Vec<usize>,
+	redo: Vec<usize>,
+}
+
+impl Journal {
+	// Plays everything `store` has recorded into `company`.
+	pub fn open(store: Box<dyn DirectoryStore>, company: &mut Company) -> Result<Journal, StoreError> {
+		let mut journal = Journal {
+			store,
+			events: Vec::new(),
+			undo: Vec::new(),
+			redo: Vec::new(),
+		};
+
+		for (number, event) in journal.store.load()?.into_iter().enumerate() {
+			for change in &event.changes {
+				company.apply(change).map_err(|e| StoreError::Log(number + 1, e.to_string()))?;
+			}
+			journal.push(event);
+		}
+		company.take_changes();
+
+		Ok(journal)
+	}
+
+	pub fn describe(&self) -> String {
+		self.store.describe()
+	}
+
+	pub fn events(&self) -> &[Event] {
+		&self.events
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.events.is_empty()
+	}
+
+	// The event redo would play forwards again next.
+	pub fn next_redo(&self) -> Option<&Event> {
+		self.redo.last().map(|&index| &self.events[index])
+	}
+
+	// Logs whatever `company` changed since the last event, if anything,
+	// and saves it. Returns whether there was something to log.
+	pub fn record(&mut self, company: &mut Company, action: &str) -> Result<bool, StoreError> {
+		let changes = company.take_changes();
+		if changes.is_empty() {
+			return Ok(false);
+		}
+
+		self.append(company, EventKind::Change, action.to_string(), changes)?;
+		Ok(true)
+	}
+
+	// Plays the last change backwards. Returns what was undone, or `None`
+	// when there's nothing left to undo.
+	pub fn undo(&mut self, company: &mut Company) -> Result<Option<String>, StoreError> {
+		let index = match self.undo.last() {
+			Some(&index) => index,
+			None => return Ok(None),
+		};
+
+		let inverse: Vec<Change> = self.events[index].changes.iter().rev().map(Change::inverse).collect();
+		self.replay(company, EventKind::Undo, index, &inverse)
+	}
+
+	// Plays the last undone change forwards again.
+	pub fn redo(&mut self, company: &mut Company) -> Result<Option<String>, StoreError> {
+		let index = match self.redo.last() {
+			Some(&index) => index,
+			None => return Ok(None),
+		};
+
+		let changes = self.events[index].changes.clone();
+		self.replay(company, EventKind::Redo, index, &changes)
+	}
+
+	fn replay(
+		&mut self,
+		company: &mut Company,
+		kind: EventKind,
+		index: usize,
+		changes: &[Change],
+	) -> Result<Option<String>, StoreError> {
+		let action = self.events[index].action.clone();
+
+		for change in changes {
+			if let Err(e) = company.apply(change) {
+				// Whatever did change still has to go in the log.
+				self.record(company, &format!("part of {} {}", kind_name(kind), action))?;
+				return Err(e.into());
+			}
+		}
+
+		let changes = company.take_changes();
+		self.append(company, kind, action.clone(), changes)?;
+		Ok(Some(action))
+	}
+
+	// Saves an event whose changes are already in `company`. If the store
+	// turns it down they're played backwards again, so `company` never
+	// holds anything the store doesn't.
+	fn append(
+		&mut self,
+		company: &mut Company,
+		kind: EventKind,
+		action: String,
+		changes: Vec<Change>,
+	) -> Result<(), StoreError> {
+		let event = Event::new(kind, action, changes);
+		if let Err(e) = self.store.record(&event, company) {
+			company.take_back(&event.changes);
+			return Err(e);
+		}
+
+		self.push(event);
+		Ok(())
+	}
+
+	// Adds an event that has already happened and keeps the undo and redo
+	// stacks in step with it.
+	fn push(&mut self, event: Event) {
+		let index = self.events.len();
+
+		match event.kind {
+			EventKind::Change => {
+				self.undo.push(index);
+				self.redo.clear();
+			},
+			EventKind::Undo => {
+				if let Some(undone) = self.undo.pop() {
+					self.redo.push(undone);
+				}
+			},
+			EventKind::Redo => {
+				if let Some(redone) = self.redo.pop() {
+					self.undo.push(redone);
+				}
+			},
+		}
+
+		self.events.push(event);
+	}
+}
+
+pub fn kind_name(kind: EventKind) -> &'static str {
+	match kind {
+		EventKind::Change => "change",
+		EventKind::Undo => "undo",
+		EventKind::Redo => "redo",
+	}
+}
+
+pub fn kind_from_name(name: &str) -> Option<EventKind> {
+	match name {
+		"change" => Some(EventKind::Change),
+		"undo" => Some(EventKind::Undo),
+		"redo" => Some(EventKind::Redo),
+		_ => None,
+	}
+}
@@ -0,0 +1,475 @@
+// http:
+
+// The directory as a small JSON web service, so other tools can ask
+// who works where without going through the text interface:
+//
+//     GET    /departments           every department and its members
+//     GET    /departments/{name}    one department
+//     POST   /employees             add {"name": "Sally", "department": "Sales"},
+//                                    optionally with "title", "start_date", "email",
+//                                    "location", "manager" and "attributes"
+//     DELETE /employees/{id}        remove an employee
+//
+// Requests are handled one at a time on a plain `TcpListener`, which is
+// plenty for a handful of internal tools. They all act as the user who
+// started the service, so changes need that user to be an editor.
+// ====================================================================
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::access::{Access, Role};
+use super::company::{Company, CompanyError, Details, Employee, EmployeeId, Orphans};
+use super::history::Journal;
+use super::storage::StoreError;
+
+// Nobody gets to send us more than this, headers or body.
+const MAX_REQUEST: usize = 64 * 1024;
+
+pub struct Request {
+	pub method: String,
+	pub path: String,
+	pub body: String,
+}
+
+pub struct Response {
+	pub status: u16,
+	pub body: Value,
+}
+
+impl Response {
+	fn ok(body: Value) -> Response {
+		Response { status: 200, body }
+	}
+
+	fn error(status: u16, message: impl ToString) -> Response {
+		Response {
+			status,
+			body: json!({ "error": message.to_string() }),
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct NewEmployee {
+	name: String,
+	#[serde(default)]
+	department: Option<String>,
+	#[serde(default)]
+	departments: Vec<String>,
+	#[serde(default)]
+	manager: Option<EmployeeId>,
+	#[serde(flatten)]
+	details: Details,
+}
+
+// Answers requests until the listener fails. Every request that changes
+// the directory goes in the history log, just like a typed command.
+pub fn serve(listener: TcpListener, company: &mut Company, journal: &mut Journal, access: &Access) -> io::Result<()> {
+	for stream in listener.incoming() {
+		let mut stream = stream?;
+
+		let request = match read_request(&mut stream) {
+			Ok(request) => request,
+			Err(e) => {
+				// A client that sent nonsense or hung up doesn't stop the server.
+				let _ = write_response(&mut stream, &Response::error(400, e));
+				continue;
+			},
+		};
+
+		// Whatever couldn't be saved has been taken back again, so the
+		// client is told it didn't happen. Once someone else has changed
+		// the directory nothing more can be saved, so the server stops.
+		let saved = respond(company, journal, access, &request);
+		let response = match &saved {
+			Ok(response) => response,
+			Err(e) => {
+				eprintln!("Couldn't save the change: {}", e);
+				let status = if matches!(e, StoreError::Conflict) { 409 } else { 500 };
+				&Response::error(status, e)
+			},
+		};
+		println!("{} {} -> {}", request.method, request.path, response.status);
+
+		if let Err(e) = write_response(&mut stream, response) {
+			eprintln!("Couldn't answer {} {}: {}", request.method, request.path, e);
+		}
+
+		if let Err(e @ StoreError::Conflict) = saved {
+			return Err(io::Error::other(e.to_string()));
+		}
+	}
+
+	Ok(())
+}
+
+// Answers one request as the user `access` is for, and puts whatever it
+// changed in the history log. If that can't be saved, the change is taken
+// back and the error comes back instead.
+pub fn respond(
+	company: &mut Company,
+	journal: &mut Journal,
+	access: &Access,
+	request: &Request,
+) -> Result<Response, StoreError> {
+	let action = format!("{} {}", request.method, request.path);
+	let response = match access.check(needed(request), &action) {
+		Ok(()) => handle(company, request),
+		Err(e) => Response::error(403, e),
+	};
+
+	journal.record(company, &action)?;
+	Ok(response)
+}
+
+// Reading is open to anyone; anything else changes the directory.
+fn needed(request: &Request) -> Role {
+	match request.method.as_str() {
+		"GET" | "HEAD" => Role::Viewer,
+		_ => Role::Editor,
+	}
+}
+
+// Works out the answer to one request. Nothing in here touches the network.
+pub fn handle(company: &mut Company, request: &Request) -> Response {
+	let path = request.path.split('?').next().unwrap_or("");
+	let segments: Vec<String> = path
+		.split('/')
+		.filter(|segment| !segment.is_empty())
+		.map(percent_decode)
+		.collect();
+	let segments: Vec<&str> = segments.iter().map(|segment| segment.as_str()).collect();
+
+	match (request.method.as_str(), segments.as_slice()) {
+		("GET", ["departments"]) => {
+			let departments: Vec<Value> = company
+				.list_all_by_department()
+				.into_iter()
+				.map(|(name, employees)| department_json(company, name, employees))
+				.collect();
+			Response::ok(json!({ "departments": departments }))
+		},
+		("GET", ["departments", name]) => {
+			if company.has_department(name) {
+				Response::ok(department_json(company, name, company.list_department(name)))
+			} else {
+				Response::error(404, format!("there is no department called {}", name))
+			}
+		},
+		("POST", ["employees"]) => add_employee(company, &request.body),
+		("DELETE", ["employees", id]) => match id.trim_start_matches('#').parse() {
+			Ok(id) => match company.remove(id) {
+				Ok(employee) => Response::ok(json!(employee)),
+				Err(e) => Response::error(404, e),
+			},
+			Err(_) => Response::error(400, format!("'{}' is not an employee id", id)),
+		},
+		(_, ["departments"]) | (_, ["departments", _]) | (_, ["employees"]) | (_, ["employees", _]) => {
+			Response::error(405, format!("{} isn't allowed on {}", request.method, path))
+		},
+		_ => Response::error(404, format!("nothing at {}", path)),
+	}
+}
+
+fn add_employee(company: &mut Company, body: &str) -> Response {
+	let new: NewEmployee = match serde_json::from_str(body) {
+		Ok(new) => new,
+		Err(e) => return Response::error(400, format!("invalid JSON: {}", e)),
+	};
+
+	let mut departments = new.departments;
+	let manager = new.manager;
+	let details = new.details;
+	departments.extend(new.department);
+	let created: Vec<&String> = departments
+		.iter()
+		.filter(|department| !company.has_department(department.trim()))
+		.collect();
+
+	let id = match company.hire(&new.name) {
+		Ok(id) => id,
+		Err(e) => return Response::error(400, e),
+	};
+
+	let result = departments
+		.iter()
+		.try_for_each(|department| company.assign(id, department))
+		.and_then(|_| company.set_manager(id, manager))
+		.and_then(|_| company.set_details(id, &details));
+
+	// Half an employee is worse than none. Nothing is left of them, or of
+	// the departments made for them, so there's nothing for the history
+	// log either.
+	if let Err(e) = result {
+		let _ = company.remove(id);
+		for department in created {
+			let _ = company.delete_department(department.trim(), &Orphans::Refuse);
+		}
+		company.take_changes();
+		let status = match e {
+			CompanyError::UnknownEmployee(_) => 404,
+			_ => 400,
+		};
+		return Response::error(status, e);
+	}
+
+	Response {
+		status: 201,
+		body: json!(company.get(id)),
+	}
+}
+
+fn department_json(company: &Company, name: &str, employees: Vec<&Employee>) -> Value {
+	json!({
+		"name": name,
+		"headcount": employees.len(),
+		"limit": company.department_limit(name),
+		"employees": employees,
+	})
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+	stream.set_read_timeout(Some(Duration::from_secs(5))).map_err(|e| e.to_string())?;
+	let mut reader = BufReader::new(stream.take(MAX_REQUEST as u64));
+
+	let mut line = String::new();
+	reader.read_line(&mut line).map_err(|e| e.to_string())?;
+
+	let mut parts = line.split_whitespace();
+	let (method, path) = match (parts.next(), parts.next()) {
+		(Some(method), Some(path)) => (method.to_uppercase(), path.to_string()),
+		_ => return Err(String::from("malformed request line")),
+	};
+
+	let mut length = 0;
+	loop {
+		let mut header = String::new();
+		if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+			return Err(String::from("the request ended inside the headers"));
+		}
+
+		let header = header.trim();
+		if header.is_empty() {
+			break;
+		}
+
+		if let Some((name, value)) = header.split_once(':') {
+			if name.trim().eq_ignore_ascii_case("content-length") {
+				length = value.trim().parse().map_err(|_| String::from("bad Content-Length"))?;
+			}
+		}
+	}
+
+	if length > MAX_REQUEST {
+		return Err(String::from("the request body is too big"));
+	}
+
+	let mut body = vec![0; length];
+	reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+	let body = String::from_utf8(body).map_err(|_| String::from("the body isn't UTF-8"))?;
+
+	Ok(Request { method, path, body })
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+	let body = response.body.to_string();
+	write!(
+		stream,
+		"HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+		response.status,
+		reason(response.status),
+		body.len(),
+		body
+	)?;
+	stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+	match status {
+		200 => "OK",
+		201 => "Created",
+		400 => "Bad Request",
+		403 => "Forbidden",
+		404 => "Not Found",
+		405 => "Method Not Allowed",
+		409 => "Conflict",
+		500 => "Internal Server Error",
+		_ => "",
+	}
+}
+
+// Turns `Customer%20Support` (or `Customer+Support`) back into `Customer Support`.
+fn percent_decode(segment: &str) -> String {
+	let bytes = segment.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+
+	while i < bytes.len() {
+		let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+
+		match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+			(b'%', Some(byte)) => {
+				decoded.push(byte);
+				i += 2;
+			},
+			(b'+', _) => decoded.push(b' '),
+			(byte, _) => decoded.push(byte),
+		}
+		i += 1;
+	}
+
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::path::PathBuf;
+
+	use super::*;
+	use crate::employees::store::MemoryStore;
+
+	struct Service {
+		company: Company,
+		journal: Journal,
+		dir: PathBuf,
+	}
+
+	impl Service {
+		// Sally and Amir in Sales, with Sally as Amir's manager.
+		fn new(name: &str) -> Service {
+			let dir = env::temp_dir().join(format!("collections-http-{}-{}", std::process::id(), name));
+			fs::create_dir_all(&dir).unwrap();
+
+			let mut company = Company::new();
+			let mut journal = Journal::open(Box::new(MemoryStore::default()), &mut company).unwrap();
+			let sally = company.add("Sally", "Sales").unwrap();
+			let amir = company.add("Amir", "Sales").unwrap();
+			company.set_manager(amir, Some(sally)).unwrap();
+			journal.record(&mut company, "setup").unwrap();
+
+			Service { company, journal, dir }
+		}
+
+		// Access as `role`, or as a viewer for `None`.
+		fn access(&self, role: Option<&str>) -> Access {
+			let users = self.dir.join("users.json");
+			let config = match role {
+				Some(role) => json!({ "users": { "kim": role } }),
+				None => json!({ "users": {} }),
+			};
+			fs::write(&users, config.to_string()).unwrap();
+			Access::load(&users, String::from("kim")).unwrap()
+		}
+
+		fn send(&mut self, role: Option<&str>, method: &str, path: &str, body: Value) -> Response {
+			let access = self.access(role);
+			let request = Request {
+				method: method.to_string(),
+				path: path.to_string(),
+				body: body.to_string(),
+			};
+			respond(&mut self.company, &mut self.journal, &access, &request).unwrap()
+		}
+	}
+
+	impl Drop for Service {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.dir);
+		}
+	}
+
+	#[test]
+	fn lists_every_department() {
+		let mut service = Service::new("list");
+		let response = service.send(None, "GET", "/departments", Value::Null);
+
+		assert_eq!(response.status, 200);
+		let departments = response.body["departments"].as_array().unwrap();
+		assert_eq!(departments.len(), 1);
+		assert_eq!(departments[0]["name"], "Sales");
+		assert_eq!(departments[0]["headcount"], 2);
+	}
+
+	#[test]
+	fn shows_one_department() {
+		let mut service = Service::new("one");
+
+		let response = service.send(None, "GET", "/departments/Sales", Value::Null);
+		assert_eq!(response.status, 200);
+		assert_eq!(response.body["employees"][0]["name"], "Amir");
+
+		let response = service.send(None, "GET", "/departments/Customer%20Support", Value::Null);
+		assert_eq!(response.status, 404);
+	}
+
+	#[test]
+	fn adds_an_employee() {
+		let mut service = Service::new("add");
+		let body = json!({
+			"name": "Kim",
+			"department": "Ops",
+			"manager": 1,
+			"title": "Lead",
+			"attributes": { "Desk": "4B" },
+		});
+		let response = service.send(Some("editor"), "POST", "/employees", body);
+
+		assert_eq!(response.status, 201);
+		assert_eq!(response.body["id"], 3);
+		assert_eq!(response.body["title"], "Lead");
+		assert_eq!(response.body["attributes"]["desk"], "4B");
+		assert!(service.company.has_department("Ops"));
+		assert_eq!(service.journal.events().len(), 2);
+	}
+
+	#[test]
+	fn adding_with_an_unknown_manager_leaves_nothing_behind() {
+		let mut service = Service::new("rollback");
+		let body = json!({ "name": "Kim", "department": "Ops", "manager": 99 });
+		let response = service.send(Some("editor"), "POST", "/employees", body);
+
+		assert_eq!(response.status, 404);
+		assert_eq!(service.company.employees().len(), 2);
+		assert!(!service.company.has_department("Ops"));
+		assert_eq!(service.journal.events().len(), 1);
+	}
+
+	#[test]
+	fn removes_an_employee() {
+		let mut service = Service::new("remove");
+
+		let response = service.send(Some("editor"), "DELETE", "/employees/1", Value::Null);
+		assert_eq!(response.status, 200);
+		assert_eq!(response.body["name"], "Sally");
+		assert!(service.company.get(1).is_none());
+		assert_eq!(service.company.get(2).unwrap().manager, None);
+
+		let response = service.send(Some("editor"), "DELETE", "/employees/1", Value::Null);
+		assert_eq!(response.status, 404);
+		let response = service.send(Some("editor"), "DELETE", "/employees/sally", Value::Null);
+		assert_eq!(response.status, 400);
+	}
+
+	#[test]
+	fn viewers_can_read_but_not_change() {
+		let mut service = Service::new("viewer");
+
+		assert_eq!(service.send(None, "GET", "/departments", Value::Null).status, 200);
+
+		let body = json!({ "name": "Kim", "department": "Ops" });
+		assert_eq!(service.send(None, "POST", "/employees", body).status, 403);
+		assert_eq!(service.send(None, "DELETE", "/employees/1", Value::Null).status, 403);
+
+		assert_eq!(service.company.employees().len(), 2);
+		assert_eq!(service.journal.events().len(), 1);
+		let denied = fs::read_to_string(service.dir.join("denied.jsonl")).unwrap();
+		assert_eq!(denied.lines().count(), 2);
+	}
+}
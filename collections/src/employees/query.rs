@@ -0,0 +1,68 @@
+// query:
+
+// Narrows a list of employees down, as in
+// `List Engineering hired after 2024-01-01 where location=Berlin`,
+// and sorts them into groups, as in `Group by location`.
+// ====================================================================
+
+use super::company::{field_name, Employee};
+use super::date::Date;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+	// Only employees who started strictly after or before these dates.
+	pub hired_after: Option<Date>,
+	pub hired_before: Option<Date>,
+	// Field and value pairs that must all match, ignoring case.
+	pub conditions: Vec<(String, String)>,
+}
+
+impl Filter {
+	// True when the filter lets everyone through.
+	pub fn is_empty(&self) -> bool {
+		*self == Filter::default()
+	}
+
+	pub fn matches(&self, employee: &Employee) -> bool {
+		// Without a start date, nobody can say when they were hired.
+		let started = employee.details.start_date;
+		let after = self.hired_after.is_none_or(|after| started.is_some_and(|start| start > after));
+		let before = self.hired_before.is_none_or(|before| started.is_some_and(|start| start < before));
+		if !(after && before) {
+			return false;
+		}
+
+		self.conditions.iter().all(|(field, value)| {
+			employee
+				.field(field)
+				.is_some_and(|actual| actual.to_lowercase() == value.to_lowercase())
+		})
+	}
+
+	pub fn apply<'a>(&self, employees: Vec<&'a Employee>) -> Vec<&'a Employee> {
+		employees.into_iter().filter(|employee| self.matches(employee)).collect()
+	}
+}
+
+// Employees by the value of one field, in alphabetical order of value,
+// with anyone who doesn't have the field at all in a group of their own
+// at the end.
+pub fn group_by<'a>(employees: Vec<&'a Employee>, field: &str) -> Vec<(Option<String>, Vec<&'a Employee>)> {
+	let field = field_name(field);
+	let mut groups: Vec<(Option<String>, Vec<&Employee>)> = Vec::new();
+
+	for employee in employees {
+		let value = employee.field(&field);
+		match groups.iter_mut().find(|(group, _)| *group == value) {
+			Some((_, members)) => members.push(employee),
+			None => groups.push((value, vec![employee])),
+		}
+	}
+
+	// `None` sorts first, so flip it to come last.
+	groups.sort_by(|(a, _), (b, _)| (a.is_none(), a).cmp(&(b.is_none(), b)));
+	for (_, members) in &mut groups {
+		members.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+	}
+	groups
+}
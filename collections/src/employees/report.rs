@@ -0,0 +1,215 @@
+// report:
+
+// Renders the roster (every department, who is in it, and headcounts)
+// as an aligned plain-text table, as Markdown, or as a standalone HTML
+// page that can be opened in a browser or mailed around.
+// ====================================================================
+
+use super::company::{Company, Employee};
+use super::query::Filter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Text,
+	Markdown,
+	Html,
+}
+
+impl Format {
+	pub fn from_name(name: &str) -> Option<Format> {
+		match name.to_lowercase().as_str() {
+			"text" | "table" => Some(Format::Text),
+			"markdown" | "md" => Some(Format::Markdown),
+			"html" => Some(Format::Html),
+			_ => None,
+		}
+	}
+}
+
+// One department's part of the roster.
+struct Section {
+	title: String,
+	rows: Vec<[String; 4]>,
+}
+
+const HEADINGS: [&str; 4] = ["ID", "Name", "Title", "Manager"];
+
+// Every department, then anyone who isn't in one, then the totals.
+// With a filter, only the employees it matches are counted and shown,
+// and departments with none of them are left out.
+pub fn roster(company: &Company, filter: &Filter, format: Format) -> String {
+	let mut sections: Vec<Section> = company
+		.list_all_by_department()
+		.into_iter()
+		.map(|(department, employees)| (department, filter.apply(employees)))
+		.filter(|(_, employees)| filter.is_empty() || !employees.is_empty())
+		.map(|(department, employees)| section(company, department, employees))
+		.collect();
+	let departments = sections.len();
+
+	let unassigned = filter.apply(company.unassigned());
+	if !unassigned.is_empty() {
+		sections.push(section(company, "No department", unassigned));
+	}
+
+	// People in several departments are only counted once here.
+	let employees = filter.apply(company.employees()).len();
+	let total = format!(
+		"{} employee{} in {} department{}",
+		employees,
+		plural(employees),
+		departments,
+		plural(departments)
+	);
+
+	render("Roster", &sections, &total, format)
+}
+
+// Just one department, for `List <department>`.
+pub fn department(company: &Company, department: &str, filter: &Filter, format: Format) -> String {
+	let employees = filter.apply(company.list_department(department));
+	let total = format!("{} employee{}", employees.len(), plural(employees.len()));
+	let sections = [section(company, department, employees)];
+
+	render(department, &sections, &total, format)
+}
+
+fn section(company: &Company, department: &str, employees: Vec<&Employee>) -> Section {
+	let rows = employees
+		.iter()
+		.map(|employee| {
+			let manager = employee
+				.manager
+				.and_then(|manager| company.get(manager))
+				.map(|manager| manager.to_string())
+				.unwrap_or_default();
+			let title = employee.details.title.clone().unwrap_or_default();
+			[format!("#{}", employee.id), employee.name.clone(), title, manager]
+		})
+		.collect::<Vec<_>>();
+
+	let title = match company.department_limit(department) {
+		Some(limit) => format!("{} ({}, limit {})", department, rows.len(), limit),
+		None => format!("{} ({})", department, rows.len()),
+	};
+
+	Section {
+		title,
+		rows,
+	}
+}
+
+fn plural(count: usize) -> &'static str {
+	if count == 1 {
+		""
+	} else {
+		"s"
+	}
+}
+
+fn render(title: &str, sections: &[Section], total: &str, format: Format) -> String {
+	match format {
+		Format::Text => text(sections, total),
+		Format::Markdown => markdown(title, sections, total),
+		Format::Html => html(title, sections, total),
+	}
+}
+
+// Columns line up across every section, so the whole roster reads as one table.
+fn text(sections: &[Section], total: &str) -> String {
+	let mut widths = HEADINGS.map(|heading| heading.chars().count());
+	for row in sections.iter().flat_map(|section| &section.rows) {
+		for (width, cell) in widths.iter_mut().zip(row) {
+			*width = (*width).max(cell.chars().count());
+		}
+	}
+
+	let rule = widths.map(|width| "-".repeat(width));
+
+	let mut out = String::new();
+	for section in sections {
+		out.push_str(&section.title);
+		out.push('\n');
+		out.push_str(&text_line(&HEADINGS, &widths));
+		out.push_str(&text_line(&rule, &widths));
+		for row in &section.rows {
+			out.push_str(&text_line(row, &widths));
+		}
+		out.push('\n');
+	}
+	out.push_str(&format!("Total: {}\n", total));
+	out
+}
+
+fn text_line(cells: &[impl AsRef<str>], widths: &[usize]) -> String {
+	let padded: Vec<String> = cells
+		.iter()
+		.zip(widths)
+		.map(|(cell, width)| format!("{:<width$}", cell.as_ref(), width = width))
+		.collect();
+	format!("  {}\n", padded.join("  ").trim_end())
+}
+
+fn markdown(title: &str, sections: &[Section], total: &str) -> String {
+	let escape = |cell: &str| cell.replace('|', "\\|").replace('<', "&lt;");
+
+	let mut out = format!("# {}\n\n", escape(title));
+	for section in sections {
+		out.push_str(&format!("## {}\n\n", escape(&section.title)));
+		out.push_str(&format!("| {} |\n", HEADINGS.join(" | ")));
+		out.push_str("|---:|------|-------|---------|\n");
+		for row in &section.rows {
+			let cells: Vec<String> = row.iter().map(|cell| escape(cell)).collect();
+			out.push_str(&format!("| {} |\n", cells.join(" | ")));
+		}
+		out.push('\n');
+	}
+	out.push_str(&format!("**Total:** {}\n", total));
+	out
+}
+
+fn html(title: &str, sections: &[Section], total: &str) -> String {
+	let mut out = format!(
+		"<!DOCTYPE html>\n\
+		<html lang=\"en\">\n\
+		<head>\n\
+		<meta charset=\"utf-8\">\n\
+		<title>{title}</title>\n\
+		<style>\n\
+		body {{ font-family: sans-serif; margin: 2em; }}\n\
+		table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+		th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }}\n\
+		th {{ background: #f0f0f0; }}\n\
+		</style>\n\
+		</head>\n\
+		<body>\n\
+		<h1>{title}</h1>\n",
+		title = escape_html(title)
+	);
+
+	for section in sections {
+		out.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", escape_html(&section.title)));
+		for heading in &HEADINGS {
+			out.push_str(&format!("<th>{}</th>", heading));
+		}
+		out.push_str("</tr>\n");
+		for row in &section.rows {
+			out.push_str("<tr>");
+			for cell in row {
+				out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+			}
+			out.push_str("</tr>\n");
+		}
+		out.push_str("</table>\n");
+	}
+
+	out.push_str(&format!("<p><strong>Total:</strong> {}</p>\n</body>\n</html>\n", escape_html(total)));
+	out
+}
+
+fn escape_html(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
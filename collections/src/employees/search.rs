@@ -0,0 +1,150 @@
+// search:
+
+// Helps with names that are nearly right. `suggest` finds the closest
+// spelling from a list using edit distance, so "engneering" can be met
+// with "did you mean Engineering?", and `Completer` fills in names and
+// departments when tab is pressed at the prompt.
+// ====================================================================
+
+use rustyline::completion::{Completer as Complete, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+use super::company::{Company, Employee, FIELDS};
+
+// The words a line can start with, for completing the first word.
+const COMMANDS: [&str; 26] = [
+	"Add", "Remove", "Move", "List", "Group", "Show", "Set", "Unset", "Roster", "Find", "Create", "Rename", "Merge",
+	"Delete", "Limit", "Report", "Chain", "Reports", "Chart", "Import", "Export", "Undo", "Redo", "History", "Help",
+	"Quit",
+];
+
+// Keywords that can stand where a name would.
+const KEYWORDS: [&str; 11] = ["all", "nobody", "hired", "after", "before", "where", "by", "into", "keep", "none", "move"];
+
+// The number of single-character edits (insert, delete or replace) to turn
+// one string into the other, ignoring case.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+	let a: Vec<char> = a.to_lowercase().chars().collect();
+	let b: Vec<char> = b.to_lowercase().chars().collect();
+
+	// Only the previous row of the table is needed to fill in the next.
+	let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+	for (i, a_char) in a.iter().enumerate() {
+		let mut current = vec![i + 1];
+		for (j, b_char) in b.iter().enumerate() {
+			let replace = previous[j] + if a_char == b_char { 0 } else { 1 };
+			let insert = current[j] + 1;
+			let delete = previous[j + 1] + 1;
+			current.push(replace.min(insert).min(delete));
+		}
+		previous = current;
+	}
+
+	previous[b.len()]
+}
+
+// The candidate closest to `input`, if any is close enough to be a typo:
+// about one mistake for every three letters. Exact matches don't count,
+// but a match that only differs in case does.
+pub fn suggest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+	let allowed = (input.chars().count() / 3).max(1);
+
+	candidates
+		.into_iter()
+		.filter(|candidate| *candidate != input)
+		.map(|candidate| (edit_distance(input, candidate), candidate))
+		.filter(|(distance, _)| *distance <= allowed)
+		.min()
+		.map(|(_, candidate)| candidate)
+}
+
+// Employees whose name, or any word of it, starts with `prefix`, ignoring case.
+pub fn find_prefix<'a>(company: &'a Company, prefix: &str) -> Vec<&'a Employee> {
+	let prefix = prefix.to_lowercase();
+
+	let mut found: Vec<&Employee> = company
+		.employees()
+		.into_iter()
+		.filter(|employee| {
+			let name = employee.name.to_lowercase();
+			name.starts_with(&prefix) || name.split_whitespace().any(|word| word.starts_with(&prefix))
+		})
+		.collect();
+	found.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+	found
+}
+
+// Tab completion for the prompt. It keeps its own copy of the names,
+// so it has to be given the company again after every change.
+#[derive(Default)]
+pub struct Completer {
+	words: Vec<String>,
+}
+
+impl Completer {
+	pub fn update(&mut self, company: &Company) {
+		let mut words: Vec<String> = company.department_names().into_iter().map(String::from).collect();
+		for employee in company.employees() {
+			words.push(employee.name.clone());
+			// Field names too, for `Set`, `where` and `Group by`.
+			words.extend(employee.details.attributes.keys().cloned());
+		}
+		words.extend(FIELDS.iter().map(|field| field.to_string()));
+		words.sort();
+		words.dedup();
+		self.words = words;
+	}
+}
+
+impl Complete for Completer {
+	type Candidate = Pair;
+
+	fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+		let before = &line[..pos];
+
+		// Inside an open quote the word started at the quote, and can have
+		// spaces in it; otherwise it started after the last space.
+		let quotes = before.matches('"').count();
+		let start = if quotes % 2 == 1 {
+			before.rfind('"').unwrap()
+		} else {
+			before.rfind(char::is_whitespace).map_or(0, |space| space + 1)
+		};
+		let word = before[start..].trim_start_matches('"').to_lowercase();
+
+		let words: Vec<&str> = if before[..start].trim().is_empty() {
+			COMMANDS.to_vec()
+		} else {
+			self.words.iter().map(|word| word.as_str()).chain(KEYWORDS.iter().copied()).collect()
+		};
+
+		let candidates = words
+			.into_iter()
+			.filter(|candidate| candidate.to_lowercase().starts_with(&word))
+			.map(|candidate| Pair {
+				display: candidate.to_string(),
+				replacement: if candidate.contains(' ') {
+					format!("\"{}\"", candidate)
+				} else {
+					candidate.to_string()
+				},
+			})
+			.collect();
+
+		Ok((start, candidates))
+	}
+}
+
+impl Hinter for Completer {
+	type Hint = String;
+}
+
+impl Highlighter for Completer {}
+
+impl Validator for Completer {}
+
+impl Helper for Completer {}
@@ -0,0 +1,254 @@
+// sqlite:
+
+// Keeps the directory in a single SQLite database. The history log is
+// the `events` and `changes` tables, and the `employees`, `departments`,
+// `memberships` and `attributes` tables hold the directory as it is
+// now. They're kept up to date change by change in the same transaction
+// as the event, so other programs can query them with plain SQL while
+// we're running.
+// The database is in WAL mode so readers never block the writer, and
+// its schema version is kept in `PRAGMA user_version`: opening an older
+// database runs whatever migrations it's missing.
+// ====================================================================
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
+
+use super::company::{Change, Company};
+use super::history::{self, Event};
+use super::storage::StoreError;
+use super::store::DirectoryStore;
+
+// Each migration takes the schema from the version before it to its own
+// position in the list, counting from 1. Only ever add to the end.
+const MIGRATIONS: [&str; 3] = [
+	"CREATE TABLE events (
+		id INTEGER PRIMARY KEY,
+		at INTEGER NOT NULL,
+		kind TEXT NOT NULL,
+		action TEXT NOT NULL
+	);
+	CREATE TABLE changes (
+		event_id INTEGER NOT NULL REFERENCES events (id),
+		position INTEGER NOT NULL,
+		change TEXT NOT NULL,
+		PRIMARY KEY (event_id, position)
+	);
+	CREATE TABLE employees (
+		id INTEGER PRIMARY KEY,
+		name TEXT NOT NULL,
+		manager INTEGER,
+		title TEXT,
+		start_date TEXT,
+		email TEXT,
+		location TEXT
+	);
+	CREATE TABLE memberships (
+		employee_id INTEGER NOT NULL REFERENCES employees (id),
+		department TEXT NOT NULL,
+		PRIMARY KEY (employee_id, department)
+	);
+	CREATE TABLE attributes (
+		employee_id INTEGER NOT NULL REFERENCES employees (id),
+		key TEXT NOT NULL,
+		value TEXT NOT NULL,
+		PRIMARY KEY (employee_id, key)
+	);",
+	"CREATE INDEX memberships_by_department ON memberships (department);
+	CREATE INDEX employees_by_name ON employees (name);
+	CREATE INDEX employees_by_manager ON employees (manager);",
+	"CREATE TABLE departments (
+		name TEXT PRIMARY KEY,
+		headcount_limit INTEGER
+	);
+	INSERT INTO departments (name) SELECT DISTINCT department FROM memberships;",
+];
+
+pub struct SqliteStore {
+	path: PathBuf,
+	connection: Connection,
+	// The last event we know of, to notice when another program has
+	// changed the directory since we loaded it.
+	last_event: i64,
+}
+
+impl SqliteStore {
+	pub fn open(path: &Path) -> Result<SqliteStore, StoreError> {
+		if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+			fs::create_dir_all(dir)?;
+		}
+
+		let mut connection = Connection::open(path)?;
+		connection.busy_timeout(Duration::from_secs(5))?;
+		connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+		connection.pragma_update(None, "foreign_keys", true)?;
+		migrate(&mut connection)?;
+
+		Ok(SqliteStore {
+			path: path.to_path_buf(),
+			connection,
+			last_event: 0,
+		})
+	}
+}
+
+fn migrate(connection: &mut Connection) -> Result<(), StoreError> {
+	let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+	if version > MIGRATIONS.len() {
+		return Err(StoreError::Schema(version));
+	}
+
+	for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+		let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+		transaction.execute_batch(migration)?;
+		transaction.pragma_update(None, "user_version", index + 1)?;
+		transaction.commit()?;
+	}
+
+	Ok(())
+}
+
+impl DirectoryStore for SqliteStore {
+	fn load(&mut self) -> Result<Vec<Event>, StoreError> {
+		let mut event_rows = self.connection.prepare("SELECT id, at, kind, action FROM events ORDER BY id")?;
+		let mut change_rows =
+			self.connection.prepare("SELECT change FROM changes WHERE event_id = ?1 ORDER BY position")?;
+
+		let mut events = Vec::new();
+		let rows = event_rows.query_map([], |row| {
+			Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get::<_, String>(2)?, row.get(3)?))
+		})?;
+
+		for (number, row) in rows.enumerate() {
+			let (id, at, kind, action) = row?;
+			let kind = history::kind_from_name(&kind)
+				.ok_or_else(|| StoreError::Log(number + 1, format!("unknown kind of event '{}'", kind)))?;
+
+			let mut changes = Vec::new();
+			for change in change_rows.query_map([id], |row| row.get::<_, String>(0))? {
+				let change: Change =
+					serde_json::from_str(&change?).map_err(|e| StoreError::Log(number + 1, e.to_string()))?;
+				changes.push(change);
+			}
+
+			events.push(Event {
+				at,
+				kind,
+				action,
+				changes,
+			});
+			self.last_event = id;
+		}
+
+		Ok(events)
+	}
+
+	fn record(&mut self, event: &Event, _company: &Company) -> Result<(), StoreError> {
+		let transaction = self.connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+		let last: i64 = transaction.query_row("SELECT coalesce(max(id), 0) FROM events", [], |row| row.get(0))?;
+		if last != self.last_event {
+			return Err(StoreError::Conflict);
+		}
+
+		transaction.execute(
+			"INSERT INTO events (at, kind, action) VALUES (?1, ?2, ?3)",
+			params![event.at, history::kind_name(event.kind), event.action],
+		)?;
+		let id = transaction.last_insert_rowid();
+
+		for (position, change) in event.changes.iter().enumerate() {
+			transaction.execute(
+				"INSERT INTO changes (event_id, position, change) VALUES (?1, ?2, ?3)",
+				params![id, position, serde_json::to_string(change)?],
+			)?;
+			apply(&transaction, change)?;
+		}
+
+		transaction.commit()?;
+		self.last_event = id;
+		Ok(())
+	}
+
+	fn describe(&self) -> String {
+		format!("Saving to the SQLite database {}", self.path.display())
+	}
+}
+
+// Brings the tables that hold the current directory up to date with one change.
+fn apply(transaction: &Transaction, change: &Change) -> Result<(), StoreError> {
+	match change {
+		Change::Hired { id, name } => {
+			transaction.execute("INSERT INTO employees (id, name) VALUES (?1, ?2)", params![id, name])?;
+		},
+		Change::Joined { id, department } => {
+			transaction.execute(
+				"INSERT INTO memberships (employee_id, department) VALUES (?1, ?2)",
+				params![id, department],
+			)?;
+		},
+		Change::Left { id, department } => {
+			transaction.execute(
+				"DELETE FROM memberships WHERE employee_id = ?1 AND department = ?2",
+				params![id, department],
+			)?;
+		},
+		Change::ManagerChanged { id, to, .. } => {
+			transaction.execute("UPDATE employees SET manager = ?2 WHERE id = ?1", params![id, to])?;
+		},
+		Change::Updated { id, field, to, .. } => {
+			let column = match field.as_str() {
+				"title" => Some("title"),
+				"start" => Some("start_date"),
+				"email" => Some("email"),
+				"location" => Some("location"),
+				_ => None,
+			};
+
+			match (column, to) {
+				(Some(column), _) => {
+					let sql = format!("UPDATE employees SET {} = ?2 WHERE id = ?1", column);
+					transaction.execute(&sql, params![id, to])?;
+				},
+				(None, Some(value)) => {
+					transaction.execute(
+						"INSERT OR REPLACE INTO attributes (employee_id, key, value) VALUES (?1, ?2, ?3)",
+						params![id, field, value],
+					)?;
+				},
+				(None, None) => {
+					transaction.execute(
+						"DELETE FROM attributes WHERE employee_id = ?1 AND key = ?2",
+						params![id, field],
+					)?;
+				},
+			}
+		},
+		Change::Removed { id, .. } => {
+			transaction.execute("DELETE FROM attributes WHERE employee_id = ?1", params![id])?;
+			transaction.execute("DELETE FROM memberships WHERE employee_id = ?1", params![id])?;
+			transaction.execute("DELETE FROM employees WHERE id = ?1", params![id])?;
+		},
+		Change::DepartmentCreated { department } => {
+			transaction.execute("INSERT INTO departments (name) VALUES (?1)", params![department])?;
+		},
+		Change::DepartmentDeleted { department } => {
+			transaction.execute("DELETE FROM departments WHERE name = ?1", params![department])?;
+		},
+		Change::DepartmentRenamed { from, to } => {
+			transaction.execute("UPDATE departments SET name = ?2 WHERE name = ?1", params![from, to])?;
+			transaction.execute("UPDATE memberships SET department = ?2 WHERE department = ?1", params![from, to])?;
+		},
+		Change::LimitChanged { department, to, .. } => {
+			transaction.execute(
+				"UPDATE departments SET headcount_limit = ?2 WHERE name = ?1",
+				params![department, to],
+			)?;
+		},
+	}
+
+	Ok(())
+}
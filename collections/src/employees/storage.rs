@@ -0,0 +1,390 @@
+// storage:
+
+// Keeps the directory in a JSON file between runs, and moves employees
+// in and out of the CSV and JSON files other tools use.
+// Every write goes to a temporary file first and is then renamed over
+// the real one, so a crash half way through never leaves a broken file.
+// ====================================================================
+
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::company::{Company, CompanyError, Details, Employee, EmployeeId};
+
+#[derive(Debug)]
+pub enum StoreError {
+	Io(io::Error),
+	Json(serde_json::Error),
+	// A CSV line that couldn't be read, counting from 1.
+	Csv(usize, String),
+	// An entry of the history log that couldn't be read or played,
+	// counting from 1.
+	Log(usize, String),
+	Sqlite(rusqlite::Error),
+	// A database from a newer version of the program, at this schema version.
+	Schema(usize),
+	// Another program changed the directory after we loaded it.
+	Conflict,
+	Company(CompanyError),
+	// Import and export go by the file extension.
+	UnknownFormat(PathBuf),
+}
+
+impl fmt::Display for StoreError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			StoreError::Io(e) => write!(f, "{}", e),
+			StoreError::Json(e) => write!(f, "invalid JSON: {}", e),
+			StoreError::Csv(line, message) => write!(f, "line {}: {}", line, message),
+			StoreError::Log(entry, message) => write!(f, "history log entry {}: {}", entry, message),
+			StoreError::Sqlite(e) => write!(f, "{}", e),
+			StoreError::Schema(version) => {
+				write!(f, "the database is at schema version {}, which is newer than this program", version)
+			},
+			StoreError::Conflict => {
+				write!(f, "someone else changed the directory since it was loaded; start again to see their changes")
+			},
+			StoreError::Company(e) => write!(f, "{}", e),
+			StoreError::UnknownFormat(path) => {
+				write!(f, "{} should end in .csv or .json", path.display())
+			},
+		}
+	}
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+	fn from(e: io::Error) -> StoreError {
+		StoreError::Io(e)
+	}
+}
+
+impl From<serde_json::Error> for StoreError {
+	fn from(e: serde_json::Error) -> StoreError {
+		StoreError::Json(e)
+	}
+}
+
+impl From<rusqlite::Error> for StoreError {
+	fn from(e: rusqlite::Error) -> StoreError {
+		StoreError::Sqlite(e)
+	}
+}
+
+impl From<CompanyError> for StoreError {
+	fn from(e: CompanyError) -> StoreError {
+		StoreError::Company(e)
+	}
+}
+
+// What goes in the save file. Files from before departments were kept
+// on their own only have the employees.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+	next_id: EmployeeId,
+	#[serde(default)]
+	departments: Vec<SavedDepartment>,
+	employees: Vec<Employee>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedDepartment {
+	name: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	limit: Option<usize>,
+}
+
+// What an import needs from a JSON file. Ids and managers are ignored,
+// since the imported employees get new ids, so an export can be
+// imported as is.
+#[derive(Deserialize)]
+struct ImportFile {
+	employees: Vec<ImportedEmployee>,
+}
+
+#[derive(Deserialize)]
+struct ImportedEmployee {
+	name: String,
+	#[serde(default)]
+	departments: BTreeSet<String>,
+	#[serde(flatten)]
+	details: Details,
+}
+
+pub fn default_path() -> PathBuf {
+	data_dir().join("collections").join("employees.json")
+}
+
+// Where the roles for `access` are kept unless another file is given.
+pub fn default_users_path() -> PathBuf {
+	data_dir().join("collections").join("users.json")
+}
+
+// The per-user data directory: %APPDATA% on Windows, otherwise
+// $XDG_DATA_HOME or the usual place under $HOME.
+fn data_dir() -> PathBuf {
+	if cfg!(windows) {
+		if let Some(appdata) = env::var_os("APPDATA") {
+			return PathBuf::from(appdata);
+		}
+	} else if let Some(xdg) = env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+		return PathBuf::from(xdg);
+	} else if let Some(home) = env::var_os("HOME") {
+		let home = PathBuf::from(home);
+		return if cfg!(target_os = "macos") {
+			home.join("Library").join("Application Support")
+		} else {
+			home.join(".local").join("share")
+		};
+	}
+
+	PathBuf::from(".")
+}
+
+// A missing file is an empty company, so the first run just works.
+pub fn load(path: &Path) -> Result<Company, StoreError> {
+	let json = match fs::read_to_string(path) {
+		Ok(json) => json,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Company::new()),
+		Err(e) => return Err(e.into()),
+	};
+
+	let saved: Saved = serde_json::from_str(&json)?;
+	let departments = saved.departments.into_iter().map(|department| (department.name, department.limit)).collect();
+	Ok(Company::restore(saved.next_id, departments, saved.employees)?)
+}
+
+pub fn save(company: &Company, path: &Path) -> Result<(), StoreError> {
+	write_atomic(path, &to_json(company))
+}
+
+// Adds everyone in a CSV or JSON file as new employees and returns how
+// many. A file with a mistake anywhere in it adds nobody.
+pub fn import(company: &mut Company, path: &Path) -> Result<usize, StoreError> {
+	let contents = fs::read_to_string(path)?;
+
+	let mark = company.pending();
+	let imported = match extension(path).as_deref() {
+		Some("csv") => import_csv(company, &contents),
+		Some("json") => import_json(company, &contents),
+		_ => Err(StoreError::UnknownFormat(path.to_path_buf())),
+	};
+	if imported.is_err() {
+		company.roll_back(mark);
+	}
+	imported
+}
+
+// Writes everyone to a CSV or JSON file and returns how many.
+pub fn export(company: &Company, path: &Path) -> Result<usize, StoreError> {
+	let contents = match extension(path).as_deref() {
+		Some("csv") => to_csv(company),
+		Some("json") => to_json(company),
+		_ => return Err(StoreError::UnknownFormat(path.to_path_buf())),
+	};
+
+	write_atomic(path, &contents)?;
+	Ok(company.employees().len())
+}
+
+fn extension(path: &Path) -> Option<String> {
+	path.extension().map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+fn write_atomic(path: &Path, contents: &str) -> Result<(), StoreError> {
+	if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+		fs::create_dir_all(dir)?;
+	}
+
+	let mut tmp = path.as_os_str().to_owned();
+	tmp.push(".tmp");
+	fs::write(&tmp, contents)?;
+	fs::rename(&tmp, path)?;
+
+	Ok(())
+}
+
+fn to_json(company: &Company) -> String {
+	let saved = Saved {
+		next_id: company.next_id(),
+		departments: company
+			.department_names()
+			.into_iter()
+			.map(|name| SavedDepartment {
+				name: name.to_string(),
+				limit: company.department_limit(name),
+			})
+			.collect(),
+		employees: company.employees().into_iter().cloned().collect(),
+	};
+	serde_json::to_string_pretty(&saved).expect("a company always serializes")
+}
+
+fn import_json(company: &mut Company, json: &str) -> Result<usize, StoreError> {
+	let file: ImportFile = serde_json::from_str(json)?;
+
+	for employee in &file.employees {
+		let id = company.hire(&employee.name)?;
+		for department in &employee.departments {
+			company.assign(id, department)?;
+		}
+		company.set_details(id, &employee.details)?;
+	}
+
+	Ok(file.employees.len())
+}
+
+// One `name,department` row per department an employee is in, and a row
+// with an empty department for anyone who isn't in one.
+fn to_csv(company: &Company) -> String {
+	let mut csv = String::from("name,department\n");
+
+	for employee in company.employees() {
+		if employee.departments.is_empty() {
+			csv.push_str(&format!("{},\n", csv_field(&employee.name)));
+		}
+		for department in &employee.departments {
+			csv.push_str(&format!("{},{}\n", csv_field(&employee.name), csv_field(department)));
+		}
+	}
+
+	csv
+}
+
+// Rows with the same name are one person in several departments, which
+// is how `to_csv` writes them. The header row is optional.
+fn import_csv(company: &mut Company, csv: &str) -> Result<usize, StoreError> {
+	let mut imported: HashMap<String, EmployeeId> = HashMap::new();
+
+	for (number, line) in csv.lines().enumerate() {
+		let number = number + 1;
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let fields = split_csv_line(line).map_err(|message| StoreError::Csv(number, message))?;
+		if fields.len() != 2 {
+			return Err(StoreError::Csv(
+				number,
+				format!("expected name,department but found {} fields", fields.len()),
+			));
+		}
+
+		let (name, department) = (fields[0].trim(), fields[1].trim());
+		if number == 1 && name.eq_ignore_ascii_case("name") && department.eq_ignore_ascii_case("department") {
+			continue;
+		}
+
+		let id = match imported.get(name) {
+			Some(&id) => id,
+			None => {
+				let id = company.hire(name).map_err(|e| StoreError::Csv(number, e.to_string()))?;
+				imported.insert(name.to_string(), id);
+				id
+			},
+		};
+
+		if !department.is_empty() {
+			company.assign(id, department).map_err(|e| StoreError::Csv(number, e.to_string()))?;
+		}
+	}
+
+	Ok(imported.len())
+}
+
+fn csv_field(value: &str) -> String {
+	if value.contains([',', '"', '\n']) {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value.to_string()
+	}
+}
+
+// Splits a line on commas. A field in double quotes can hold commas,
+// and `""` inside it is a literal quote.
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+	let mut fields = Vec::new();
+	let mut field = String::new();
+	let mut chars = line.chars().peekable();
+	let mut quoted = false;
+
+	while let Some(c) = chars.next() {
+		match c {
+			'"' if quoted && chars.peek() == Some(&'"') => {
+				field.push('"');
+				chars.next();
+			},
+			'"' if quoted => quoted = false,
+			'"' if field.trim().is_empty() => {
+				field.clear();
+				quoted = true;
+			},
+			',' if !quoted => fields.push(std::mem::take(&mut field)),
+			_ => field.push(c),
+		}
+	}
+
+	if quoted {
+		return Err(String::from("a quoted field is never closed"));
+	}
+
+	fields.push(field);
+	Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn import_file(company: &mut Company, name: &str, contents: &str) -> Result<usize, StoreError> {
+		let path = env::temp_dir().join(format!("collections-{}-{}", std::process::id(), name));
+		fs::write(&path, contents).unwrap();
+		let imported = import(company, &path);
+		fs::remove_file(&path).unwrap();
+		imported
+	}
+
+	#[test]
+	fn a_csv_with_a_bad_row_imports_nobody() {
+		let mut company = Company::new();
+		company.add("Amir", "Eng").unwrap();
+		company.take_changes();
+
+		let imported = import_file(&mut company, "bad.csv", "name,department\nSally,Eng\nBob,Ops\n\"Kim,Sales\n");
+
+		assert!(matches!(imported, Err(StoreError::Csv(4, _))));
+		assert_eq!(company.employees().len(), 1);
+		assert_eq!(company.department_names(), vec!["Eng"]);
+		assert!(company.take_changes().is_empty());
+	}
+
+	#[test]
+	fn attributes_keep_whatever_case_they_were_written_in() {
+		let mut company = Company::new();
+		let json = r#"{"employees": [{"name": "Sally", "attributes": {"Team": "Blue", "team2": "x"}}]}"#;
+
+		import_file(&mut company, "attributes.json", json).unwrap();
+
+		let sally = &company.employees()[0];
+		assert_eq!(sally.field("team").as_deref(), Some("Blue"));
+		assert_eq!(sally.field("team2").as_deref(), Some("x"));
+	}
+
+	#[test]
+	fn a_good_csv_imports_everyone() {
+		let mut company = Company::new();
+
+		let imported = import_file(&mut company, "good.csv", "name,department\nSally,Eng\nSally,Ops\nBob,\n");
+
+		assert_eq!(imported.unwrap(), 2);
+		assert_eq!(company.list_department("Ops").len(), 1);
+		assert_eq!(company.unassigned().len(), 1);
+	}
+}
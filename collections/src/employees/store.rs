@@ -0,0 +1,208 @@
+// store:
+
+// Where the directory's history is kept between runs. The journal only
+// ever needs two things from a store: every event so far, to play back
+// when it starts, and a way to add one more. Anything else a store keeps,
+// such as a copy of the current directory for other programs to read,
+// it brings up to date itself when an event is recorded.
+// There are three stores: `MemoryStore` keeps nothing past the end of
+// the run, `FileStore` keeps a JSON log and save file side by side, and
+// `SqliteStore` (in `sqlite`) keeps everything in one SQLite database.
+// ====================================================================
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::company::Company;
+use super::history::{Event, EventKind};
+use super::storage::{self, StoreError};
+
+pub trait DirectoryStore {
+	// Every event recorded so far, oldest first.
+	fn load(&mut self) -> Result<Vec<Event>, StoreError>;
+
+	// Adds `event`, which has just been applied to `company`.
+	fn record(&mut self, event: &Event, company: &Company) -> Result<(), StoreError>;
+
+	// Where things are being saved, for telling the user.
+	fn describe(&self) -> String;
+}
+
+// Keeps the events for as long as the program runs, and no longer.
+#[derive(Default)]
+pub struct MemoryStore {
+	events: Vec<Event>,
+}
+
+impl DirectoryStore for MemoryStore {
+	fn load(&mut self) -> Result<Vec<Event>, StoreError> {
+		Ok(self.events.clone())
+	}
+
+	fn record(&mut self, event: &Event, _company: &Company) -> Result<(), StoreError> {
+		self.events.push(event.clone());
+		Ok(())
+	}
+
+	fn describe(&self) -> String {
+		String::from("Nothing will be saved")
+	}
+}
+
+// The log is one JSON object per line next to the JSON save file,
+// `employees.json` getting `employees.jsonl`, and the save file is
+// rewritten after every event for the tools that just want the current
+// directory.
+pub struct FileStore {
+	file: PathBuf,
+	log: PathBuf,
+	// How long the log was when we last read or wrote it. If it has grown
+	// since, another program has added to it and our events would no
+	// longer follow on from its.
+	length: u64,
+}
+
+impl FileStore {
+	pub fn new(file: &Path) -> FileStore {
+		FileStore {
+			file: file.to_path_buf(),
+			log: file.with_extension("jsonl"),
+			length: 0,
+		}
+	}
+
+	// Returns the log, still locked, for anything that has to happen
+	// before another program gets a turn.
+	fn append(&mut self, event: &Event) -> Result<File, StoreError> {
+		if let Some(dir) = self.log.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+			fs::create_dir_all(dir)?;
+		}
+
+		let line = serde_json::to_string(event)?;
+		let mut log = OpenOptions::new().create(true).append(true).open(&self.log)?;
+		// Held until `log` is closed, so only one program appends at a time.
+		log.lock()?;
+		if log.metadata()?.len() != self.length {
+			return Err(StoreError::Conflict);
+		}
+
+		writeln!(log, "{}", line)?;
+		self.length = log.metadata()?.len();
+		Ok(log)
+	}
+}
+
+impl DirectoryStore for FileStore {
+	// A save file from before there was a log starts the log off.
+	fn load(&mut self) -> Result<Vec<Event>, StoreError> {
+		let log = match fs::read_to_string(&self.log) {
+			Ok(log) => log,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+			Err(e) => return Err(e.into()),
+		};
+		self.length = log.len() as u64;
+
+		let mut events = Vec::new();
+		for (number, line) in log.lines().enumerate() {
+			if line.trim().is_empty() {
+				continue;
+			}
+
+			let event: Event = serde_json::from_str(line).map_err(|e| StoreError::Log(number + 1, e.to_string()))?;
+			events.push(event);
+		}
+
+		if events.is_empty() {
+			let changes = storage::load(&self.file)?.take_changes();
+			if !changes.is_empty() {
+				let event = Event::new(EventKind::Change, format!("Load {}", self.file.display()), changes);
+				self.append(&event)?;
+				events.push(event);
+			}
+		}
+
+		Ok(events)
+	}
+
+	// The event only stays in the log if the save file could be written
+	// too. Otherwise it would come back on the next run, after the journal
+	// has taken it back.
+	fn record(&mut self, event: &Event, company: &Company) -> Result<(), StoreError> {
+		let before = self.length;
+		let log = self.append(event)?;
+
+		if let Err(e) = storage::save(company, &self.file) {
+			log.set_len(before)?;
+			self.length = before;
+			return Err(e);
+		}
+		Ok(())
+	}
+
+	fn describe(&self) -> String {
+		format!("Saving to {}, with the history in {}", self.file.display(), self.log.display())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+
+	use super::*;
+	use crate::employees::history::Journal;
+
+	fn scratch(name: &str) -> PathBuf {
+		let dir = env::temp_dir().join(format!("collections-store-{}-{}", std::process::id(), name));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn hire(company: &mut Company, name: &str) -> Event {
+		company.hire(name).unwrap();
+		Event::new(EventKind::Change, format!("Add {}", name), company.take_changes())
+	}
+
+	#[test]
+	fn a_second_writer_to_the_same_log_is_turned_down() {
+		let dir = scratch("conflict");
+		let file = dir.join("employees.json");
+		let mut first = FileStore::new(&file);
+		let mut second = FileStore::new(&file);
+		first.load().unwrap();
+		second.load().unwrap();
+
+		let mut company = Company::new();
+		first.record(&hire(&mut company, "Sally"), &company).unwrap();
+		let mut other = Company::new();
+		let refused = second.record(&hire(&mut other, "Amir"), &other);
+
+		assert!(matches!(refused, Err(StoreError::Conflict)));
+		assert_eq!(FileStore::new(&file).load().unwrap().len(), 1);
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn a_change_that_cannot_be_saved_is_gone_after_a_restart() {
+		let dir = scratch("unsaved");
+		let file = dir.join("employees.json");
+		let mut company = Company::new();
+		let mut journal = Journal::open(Box::new(FileStore::new(&file)), &mut company).unwrap();
+		company.add("Sally", "Eng").unwrap();
+		journal.record(&mut company, "Add Sally to Eng").unwrap();
+
+		// The save file is written through `employees.json.tmp`, which
+		// can't be written while it's a directory.
+		fs::create_dir(dir.join("employees.json.tmp")).unwrap();
+		company.add("Amir", "Eng").unwrap();
+		assert!(journal.record(&mut company, "Add Amir to Eng").is_err());
+		assert_eq!(company.employees().len(), 1);
+
+		let mut reopened = Company::new();
+		Journal::open(Box::new(FileStore::new(&file)), &mut reopened).unwrap();
+		assert_eq!(reopened.employees().len(), 1);
+		assert_eq!(reopened.employees()[0].name, "Sally");
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
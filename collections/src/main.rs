@@ -1,3 +1,19 @@
+use std::env;
+
+mod average;
+mod employees;
+mod stats;
+
+// Each exercise is run by name, e.g. `cargo run -- employees`.
 fn main() {
-    println!("Hello, world!");
+	let mut args = env::args().skip(1);
+
+	match args.next().as_deref() {
+		Some("average") => average::main(args),
+		Some("employees") => employees::main(args),
+		_ => {
+			eprintln!("{}", average::USAGE);
+			eprintln!("{}", employees::USAGE);
+		},
+	}
 }
@@ -0,0 +1,227 @@
+// stats:
+
+// Descriptive statistics over a slice of any built-in number type.
+// Results that can fall between the values, such as the mean or the
+// median of an even number of values, come back as `f64`; the ones that
+// are always one of the values, such as the mode or the minimum, keep
+// the type they were given.
+// Nothing can be said about an empty slice, so every function returns
+// `None` for one, and `percentile` returns an error.
+// Floating point NaNs sort after every other value, so they never panic
+// but do end up at the top of percentiles and the maximum.
+// ====================================================================
+
+use std::cmp::Ordering;
+use std::fmt;
+
+// A number that can be compared and turned into an `f64` for the sums.
+pub trait Number: Copy + PartialOrd {
+	fn to_f64(self) -> f64;
+}
+
+macro_rules! number {
+	($($type:ty),*) => {
+		$(
+			impl Number for $type {
+				fn to_f64(self) -> f64 {
+					self as f64
+				}
+			}
+		)*
+	};
+}
+
+number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsError {
+	Empty,
+	// Percentiles go from 0 to 100.
+	OutOfRange(f64),
+}
+
+impl fmt::Display for StatsError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			StatsError::Empty => write!(f, "there are no values"),
+			StatsError::OutOfRange(percentile) => {
+				write!(f, "the {} percentile doesn't exist, it has to be from 0 to 100", percentile)
+			},
+		}
+	}
+}
+
+impl std::error::Error for StatsError {}
+
+// The three points that split sorted values into four equal parts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quartiles {
+	pub lower: f64,
+	pub median: f64,
+	pub upper: f64,
+}
+
+pub fn mean<T: Number>(values: &[T]) -> Option<f64> {
+	if values.is_empty() {
+		return None;
+	}
+
+	let sum: f64 = values.iter().map(|value| value.to_f64()).sum();
+	Some(sum / values.len() as f64)
+}
+
+// The middle value once sorted, or halfway between the two middle ones
+// when there's an even number of them.
+pub fn median<T: Number>(values: &[T]) -> Option<f64> {
+	percentile(values, 50.0).ok()
+}
+
+// The values that come up most often, smallest first. There's more than
+// one when several are tied.
+pub fn mode<T: Number>(values: &[T]) -> Option<Vec<T>> {
+	let sorted = sorted(values);
+	let mut mode = Vec::new();
+	let mut most = 0;
+
+	for run in sorted.chunk_by(|a, b| a == b) {
+		match run.len().cmp(&most) {
+			Ordering::Greater => {
+				most = run.len();
+				mode = vec![run[0]];
+			},
+			Ordering::Equal => mode.push(run[0]),
+			Ordering::Less => {},
+		}
+	}
+
+	if mode.is_empty() {
+		None
+	} else {
+		Some(mode)
+	}
+}
+
+// How far the values are from their mean, on average, squared. This is
+// the population variance: it divides by the number of values.
+pub fn variance<T: Number>(values: &[T]) -> Option<f64> {
+	let mean = mean(values)?;
+	let squares: f64 = values.iter().map(|value| (value.to_f64() - mean).powi(2)).sum();
+	Some(squares / values.len() as f64)
+}
+
+pub fn std_dev<T: Number>(values: &[T]) -> Option<f64> {
+	variance(values).map(f64::sqrt)
+}
+
+pub fn min<T: Number>(values: &[T]) -> Option<T> {
+	values.iter().copied().min_by(|a, b| compare(*a, *b))
+}
+
+pub fn max<T: Number>(values: &[T]) -> Option<T> {
+	values.iter().copied().max_by(|a, b| compare(*a, *b))
+}
+
+// The gap between the smallest and the largest value. It's an `f64` so
+// that the gap across a whole integer type still fits.
+pub fn range<T: Number>(values: &[T]) -> Option<f64> {
+	Some(max(values)?.to_f64() - min(values)?.to_f64())
+}
+
+pub fn quartiles<T: Number>(values: &[T]) -> Option<Quartiles> {
+	Some(Quartiles {
+		lower: percentile(values, 25.0).ok()?,
+		median: percentile(values, 50.0).ok()?,
+		upper: percentile(values, 75.0).ok()?,
+	})
+}
+
+// The value `percentile` percent of the way up the sorted values, from
+// 0 for the smallest to 100 for the largest. Between two values it's
+// the point that far along the line joining them.
+pub fn percentile<T: Number>(values: &[T], percentile: f64) -> Result<f64, StatsError> {
+	if !(0.0..=100.0).contains(&percentile) {
+		return Err(StatsError::OutOfRange(percentile));
+	}
+	if values.is_empty() {
+		return Err(StatsError::Empty);
+	}
+
+	let sorted = sorted(values);
+	let rank = percentile / 100.0 * (sorted.len() - 1) as f64;
+	let below = sorted[rank.floor() as usize].to_f64();
+	let above = sorted[rank.ceil() as usize].to_f64();
+
+	// Landing right on a value gives that value, even an infinite one.
+	if rank.fract() == 0.0 {
+		return Ok(below);
+	}
+	Ok(below + (above - below) * rank.fract())
+}
+
+fn sorted<T: Number>(values: &[T]) -> Vec<T> {
+	let mut sorted = values.to_vec();
+	sorted.sort_by(|a, b| compare(*a, *b));
+	sorted
+}
+
+// Orders any two values, NaNs last whatever their sign.
+fn compare<T: Number>(a: T, b: T) -> Ordering {
+	let nan = |value: T| value.to_f64().is_nan();
+	nan(a).cmp(&nan(b)).then_with(|| a.partial_cmp(&b).unwrap_or(Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn empty_input_has_no_statistics() {
+		let empty: [i32; 0] = [];
+		assert_eq!(mean(&empty), None);
+		assert_eq!(median(&empty), None);
+		assert_eq!(mode(&empty), None);
+		assert_eq!(variance(&empty), None);
+		assert_eq!(min(&empty), None);
+		assert_eq!(max(&empty), None);
+		assert_eq!(range(&empty), None);
+		assert_eq!(quartiles(&empty), None);
+		assert_eq!(percentile(&empty, 50.0), Err(StatsError::Empty));
+	}
+
+	#[test]
+	fn median_of_an_even_count_is_halfway_between_the_middle_two() {
+		assert_eq!(median(&[6, 3, 4, 0, 2, 1, 6, 2]), Some(2.5));
+		assert_eq!(median(&[1.0, 2.0]), Some(1.5));
+	}
+
+	#[test]
+	fn quartiles_split_the_values_in_four() {
+		let quartiles = quartiles(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+		assert_eq!(
+			quartiles,
+			Quartiles {
+				lower: 3.0,
+				median: 5.0,
+				upper: 7.0,
+			}
+		);
+	}
+
+	#[test]
+	fn percentiles_go_from_the_smallest_to_the_largest() {
+		let values = [10, 40, 20, 30];
+		assert_eq!(percentile(&values, 0.0), Ok(10.0));
+		assert_eq!(percentile(&values, 100.0), Ok(40.0));
+		assert_eq!(percentile(&values, 50.0), Ok(25.0));
+		assert_eq!(percentile(&values, -1.0), Err(StatsError::OutOfRange(-1.0)));
+		assert_eq!(percentile(&values, 100.5), Err(StatsError::OutOfRange(100.5)));
+	}
+
+	#[test]
+	fn nans_sort_last_whatever_their_sign() {
+		let values = [1.0, -f64::NAN, 3.0];
+		assert_eq!(min(&values), Some(1.0));
+		assert!(max(&values).unwrap().is_nan());
+		assert_eq!(percentile(&values, 50.0), Ok(3.0));
+	}
+}
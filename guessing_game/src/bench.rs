@@ -0,0 +1,284 @@
+// Plays thousands of seeded games with automatic guessing strategies and
+// collects how many attempts each one needed. Every strategy gets the
+// same secrets, so the numbers can be compared and reproduced exactly.
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::GameConfig;
+use crate::game::{Game, GuessOutcome};
+use crate::reverse::{Answer, Solver};
+
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    // Called at the start of every game.
+    fn start(&mut self, min: u32, max: u32);
+
+    fn next_guess(&mut self, rng: &mut StdRng) -> u32;
+
+    // How the guess compared to the secret: `Less` means it was too small.
+    fn feedback(&mut self, guess: u32, ordering: Ordering);
+}
+
+pub const NAMES: [&str; 4] = ["binary", "golden", "random", "linear"];
+
+pub fn by_name(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "binary" => Some(Box::new(BinarySearch::default())),
+        "golden" => Some(Box::new(GoldenSection::default())),
+        "random" => Some(Box::new(RandomGuess::default())),
+        "linear" => Some(Box::new(Linear::default())),
+        _ => None,
+    }
+}
+
+// Halves the range every time, the same search as reverse mode.
+#[derive(Default)]
+pub struct BinarySearch {
+    solver: Option<Solver>,
+}
+
+impl Strategy for BinarySearch {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn start(&mut self, min: u32, max: u32) {
+        self.solver = Some(Solver::new(min, max));
+    }
+
+    fn next_guess(&mut self, _rng: &mut StdRng) -> u32 {
+        self.solver.as_mut().expect("started").next_guess()
+    }
+
+    fn feedback(&mut self, _guess: u32, ordering: Ordering) {
+        let answer = match ordering {
+            Ordering::Less => Answer::Higher,
+            Ordering::Greater => Answer::Lower,
+            Ordering::Equal => Answer::Correct,
+        };
+        // The game never lies, so the solver can't catch it cheating.
+        let _ = self.solver.as_mut().expect("started").answer(answer);
+    }
+}
+
+// Keeps track of the numbers that are still possible.
+#[derive(Default)]
+struct Interval {
+    low: u32,
+    high: u32,
+}
+
+impl Interval {
+    fn narrow(&mut self, guess: u32, ordering: Ordering) {
+        match ordering {
+            Ordering::Less => self.low = guess + 1,
+            Ordering::Greater => self.high = guess - 1,
+            Ordering::Equal => {}
+        }
+    }
+}
+
+// Splits the range at the golden ratio instead of the middle, the way
+// golden-section search does. It's worse than halving for this game,
+// which is the point of comparing the two.
+#[derive(Default)]
+pub struct GoldenSection {
+    interval: Interval,
+}
+
+impl Strategy for GoldenSection {
+    fn name(&self) -> &'static str {
+        "golden"
+    }
+
+    fn start(&mut self, min: u32, max: u32) {
+        self.interval = Interval { low: min, high: max };
+    }
+
+    fn next_guess(&mut self, _rng: &mut StdRng) -> u32 {
+        const RATIO: f64 = 0.381_966; // 1 - 1/phi
+        let Interval { low, high } = self.interval;
+        low + (f64::from(high - low) * RATIO).round() as u32
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        self.interval.narrow(guess, ordering);
+    }
+}
+
+// Picks any number that is still possible.
+#[derive(Default)]
+pub struct RandomGuess {
+    interval: Interval,
+}
+
+impl Strategy for RandomGuess {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn start(&mut self, min: u32, max: u32) {
+        self.interval = Interval { low: min, high: max };
+    }
+
+    fn next_guess(&mut self, rng: &mut StdRng) -> u32 {
+        rng.gen_range(self.interval.low..=self.interval.high)
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        self.interval.narrow(guess, ordering);
+    }
+}
+
+// Counts up from the bottom of the range, ignoring the hints.
+#[derive(Default)]
+pub struct Linear {
+    next: u32,
+}
+
+impl Strategy for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn start(&mut self, min: u32, _max: u32) {
+        self.next = min;
+    }
+
+    // Stays on the top of the range once it gets there, rather than
+    // counting past `u32::MAX`.
+    fn next_guess(&mut self, _rng: &mut StdRng) -> u32 {
+        let guess = self.next;
+        self.next = self.next.saturating_add(1);
+        guess
+    }
+
+    fn feedback(&mut self, _guess: u32, _ordering: Ordering) {}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub strategy: &'static str,
+    // Attempts needed for each game, in the order the games were played.
+    pub attempts: Vec<u32>,
+    // Games the strategy didn't solve within one guess per number.
+    pub unsolved: u32,
+}
+
+impl Report {
+    pub fn mean(&self) -> f64 {
+        let total: u64 = self.attempts.iter().map(|&a| u64::from(a)).sum();
+        total as f64 / self.attempts.len().max(1) as f64
+    }
+
+    pub fn median(&self) -> f64 {
+        let mut sorted = self.attempts.clone();
+        sorted.sort_unstable();
+
+        match sorted.len() {
+            0 => 0.0,
+            len if len % 2 == 1 => f64::from(sorted[len / 2]),
+            len => (f64::from(sorted[len / 2 - 1]) + f64::from(sorted[len / 2])) / 2.0,
+        }
+    }
+
+    pub fn max(&self) -> u32 {
+        self.attempts.iter().copied().max().unwrap_or(0)
+    }
+
+    // Counts of games by attempts, grouped into at most `rows` buckets.
+    // Each entry is (lowest attempts, highest attempts, games).
+    pub fn histogram(&self, rows: u32) -> Vec<(u32, u32, usize)> {
+        let counts: BTreeMap<u32, usize> =
+            self.attempts.iter().fold(BTreeMap::new(), |mut counts, &a| {
+                *counts.entry(a).or_insert(0) += 1;
+                counts
+            });
+
+        let (first, last) = match (counts.keys().next(), counts.keys().next_back()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return Vec::new(),
+        };
+
+        let width = ((last - first) / rows.max(1)) + 1;
+        let mut buckets = Vec::new();
+        let mut low = first;
+
+        while low <= last {
+            let high = low + width - 1;
+            let games = counts.range(low..=high).map(|(_, &count)| count).sum();
+            buckets.push((low, high.min(last), games));
+            low += width;
+        }
+
+        buckets
+    }
+}
+
+// Plays `games` games with each strategy. The secrets come from `seed`,
+// so every strategy faces exactly the same numbers.
+pub fn run(
+    config: GameConfig,
+    games: u32,
+    seed: u64,
+    strategies: &mut [Box<dyn Strategy>],
+) -> Vec<Report> {
+    let mut secrets = StdRng::seed_from_u64(seed);
+    let secrets: Vec<u32> = (0..games)
+        .map(|_| secrets.gen_range(config.min..=config.max))
+        .collect();
+
+    // A strategy that narrows the range never needs more guesses than
+    // there are numbers; anything past that is stuck.
+    let limit = u64::from(config.max - config.min) + 1;
+    let config = GameConfig {
+        max_attempts: None,
+        ..config
+    };
+
+    strategies
+        .iter_mut()
+        .map(|strategy| {
+            // A different stream from the secrets, or `random` would
+            // draw the very same numbers and always guess right.
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(1));
+            let mut report = Report {
+                strategy: strategy.name(),
+                attempts: Vec::with_capacity(secrets.len()),
+                unsolved: 0,
+            };
+
+            for &secret in &secrets {
+                let mut game = Game::with_secret(config, secret);
+                strategy.start(config.min, config.max);
+
+                loop {
+                    let guess = strategy.next_guess(&mut rng);
+                    let ordering = match game.guess(guess) {
+                        GuessOutcome::TooSmall => Ordering::Less,
+                        GuessOutcome::TooBig => Ordering::Greater,
+                        GuessOutcome::Correct => Ordering::Equal,
+                        GuessOutcome::OutOfAttempts => unreachable!("bench games have no attempt limit"),
+                    };
+
+                    if ordering == Ordering::Equal {
+                        report.attempts.push(game.attempts());
+                        break;
+                    }
+                    if u64::from(game.attempts()) >= limit {
+                        report.unsolved += 1;
+                        break;
+                    }
+
+                    strategy.feedback(guess, ordering);
+                }
+            }
+
+            report
+        })
+        .collect()
+}
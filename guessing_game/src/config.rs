@@ -0,0 +1,248 @@
+// Settings for a round of the guessing game, chosen from the command line.
+//
+// A `Difficulty` preset fills in the range and the number of attempts,
+// and the individual flags can then override any part of it:
+//
+//     guessing_game --difficulty hard --attempts 12
+//     guessing_game --min 1 --max 20 --attempts 4
+//
+// Other flags pick what the program does instead of a normal round,
+// such as `--scores` to print the high-score table or `--reverse`
+// to let the computer guess the player's number. `bench`, `mastermind`
+// and `wordle` are subcommands:
+//
+//     guessing_game bench --games 10000 --seed 7 --strategy binary,linear
+//     guessing_game wordle --attempts 8
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    // Every preset leaves at least one attempt more than binary search
+    // needs (6, 7 and 10 guesses for these ranges), so a careful player
+    // can always win.
+    pub fn preset(self) -> GameConfig {
+        match self {
+            Difficulty::Easy => GameConfig {
+                min: 1,
+                max: 50,
+                max_attempts: Some(10),
+            },
+            Difficulty::Normal => GameConfig {
+                min: 1,
+                max: 100,
+                max_attempts: Some(8),
+            },
+            Difficulty::Hard => GameConfig {
+                min: 1,
+                max: 1000,
+                max_attempts: Some(12),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameConfig {
+    pub min: u32,
+    pub max: u32,
+    // `None` means the player can keep guessing until they win.
+    pub max_attempts: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Play,
+    Scores,
+    Reverse,
+    // Host a multiplayer game on this port.
+    Serve(u16),
+    // Join a multiplayer game at this address.
+    Connect(String),
+    // Play the guesses in a file and print a transcript.
+    Script(PathBuf),
+    // Check that a transcript plays out the same way again.
+    Replay(PathBuf),
+    // Compare automatic guessing strategies over many games.
+    Bench,
+    // Other kinds of secret, played with the same game loop.
+    Mastermind,
+    Wordle,
+}
+
+// Everything that was asked for on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    pub game: GameConfig,
+    pub player: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    // Seeds the secret number so a game can be played again exactly.
+    pub seed: Option<u64>,
+    // Names of the hints turned on with `--hints warmer,parity`.
+    pub hints: Vec<String>,
+    // How many games `bench` plays, and with which strategies
+    // (all of them when the list is empty).
+    pub games: u32,
+    pub strategies: Vec<String>,
+    // Play in the full-screen view when running in a terminal.
+    pub tui: bool,
+    // Play today's shared puzzle instead of a random number.
+    pub daily: bool,
+    // Score the game by time taken. Attempts are unlimited unless
+    // `--attempts` is given as well.
+    pub time_attack: bool,
+}
+
+impl Options {
+    pub fn build(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        // The first argument is the name of the program.
+        args.next();
+
+        let mut mode = Mode::Play;
+        let mut player = None;
+        let mut scores_file = None;
+        let mut seed = None;
+        let mut hints = Vec::new();
+        let mut games = 10_000;
+        let mut strategies = Vec::new();
+        let mut tui = false;
+        let mut daily = false;
+        let mut time_attack = false;
+        let mut difficulty = Difficulty::Normal;
+        let mut min = None;
+        let mut max = None;
+        let mut max_attempts = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "bench" => mode = Mode::Bench,
+                "mastermind" => mode = Mode::Mastermind,
+                "wordle" => mode = Mode::Wordle,
+                "--difficulty" | "-d" => {
+                    let name = flag_value(&arg, args.next())?;
+                    difficulty = Difficulty::from_name(&name).ok_or_else(|| {
+                        format!(
+                            "unknown difficulty '{}' (expected easy, normal or hard)",
+                            name
+                        )
+                    })?;
+                }
+                "--min" => min = Some(parse_number(&arg, args.next())?),
+                "--max" => max = Some(parse_number(&arg, args.next())?),
+                "--attempts" | "-a" => {
+                    let attempts = parse_number(&arg, args.next())?;
+                    if attempts == 0 {
+                        return Err(String::from("--attempts must be at least 1"));
+                    }
+                    max_attempts = Some(attempts);
+                }
+                "--name" | "-n" => player = Some(flag_value(&arg, args.next())?),
+                "--tui" => tui = true,
+                "--daily" => daily = true,
+                "--time-attack" => time_attack = true,
+                "--scores" => mode = Mode::Scores,
+                "--reverse" | "-r" => mode = Mode::Reverse,
+                "--serve" => {
+                    let port = flag_value(&arg, args.next())?;
+                    let port = port
+                        .parse()
+                        .map_err(|_| format!("--serve expects a port number, got '{}'", port))?;
+                    mode = Mode::Serve(port);
+                }
+                "--connect" => mode = Mode::Connect(flag_value(&arg, args.next())?),
+                "--input" => mode = Mode::Script(PathBuf::from(flag_value(&arg, args.next())?)),
+                "--replay" => mode = Mode::Replay(PathBuf::from(flag_value(&arg, args.next())?)),
+                "--hints" => hints = name_list(flag_value(&arg, args.next())?),
+                "--strategy" => strategies = name_list(flag_value(&arg, args.next())?),
+                "--games" => {
+                    games = parse_number(&arg, args.next())?;
+                    if games == 0 {
+                        return Err(String::from("--games must be at least 1"));
+                    }
+                }
+                "--seed" => {
+                    let value = flag_value(&arg, args.next())?;
+                    let value = value
+                        .parse()
+                        .map_err(|_| format!("--seed expects a number, got '{}'", value))?;
+                    seed = Some(value);
+                }
+                "--scores-file" => scores_file = Some(PathBuf::from(flag_value(&arg, args.next())?)),
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+
+        let preset = difficulty.preset();
+        let default_attempts = match mode {
+            Mode::Mastermind => Some(10),
+            Mode::Wordle => Some(6),
+            _ if time_attack => None,
+            _ => preset.max_attempts,
+        };
+        let game = GameConfig {
+            min: min.unwrap_or(preset.min),
+            max: max.unwrap_or(preset.max),
+            max_attempts: max_attempts.or(default_attempts),
+        };
+
+        if game.min >= game.max {
+            return Err(format!(
+                "the range {}..={} is empty or has a single number; --min must be below --max",
+                game.min, game.max
+            ));
+        }
+
+        if daily && seed.is_some() {
+            return Err(String::from("--daily picks its own seed, so it can't be used with --seed"));
+        }
+
+        Ok(Options {
+            mode,
+            game,
+            player,
+            scores_file,
+            seed,
+            hints,
+            games,
+            strategies,
+            tui,
+            daily,
+            time_attack,
+        })
+    }
+}
+
+fn flag_value(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} needs a value", flag))
+}
+
+// Splits `a, B,c` into ["a", "b", "c"].
+fn name_list(value: String) -> Vec<String> {
+    value
+        .split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn parse_number(flag: &str, value: Option<String>) -> Result<u32, String> {
+    let value = flag_value(flag, value)?;
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a positive number, got '{}'", flag, value))
+}
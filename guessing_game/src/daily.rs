@@ -0,0 +1,64 @@
+// The daily puzzle and the summary players can share afterwards.
+//
+// The secret for a day comes from a seed worked out from the date and the
+// range, so everyone playing `--daily` with the same settings on the same
+// (UTC) day gets the same number without any server involved.
+use std::time::Duration;
+
+use crate::config::GameConfig;
+use crate::date::Date;
+use crate::game::{Game, GameStatus};
+use crate::scores::format_duration;
+
+pub fn seed(date: Date, config: &GameConfig) -> u64 {
+    // FNV-1a over the date and range: simple, and stable across
+    // platforms and Rust versions, unlike the standard library's hasher.
+    let key = format!("{} {} {}", date, config.min, config.max);
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+pub fn game(date: Date, config: GameConfig) -> Game {
+    Game::from_seed(config, seed(date, &config))
+}
+
+// A few lines describing how the game went without giving the number away:
+//
+//     Guessing game daily 2026-10-18 (1-100)
+//     4/8 in 0:37.2
+//     ⬆️⬇️⬆️✅
+//
+// Each arrow says which way the player had to go after that guess.
+pub fn share_summary(title: &str, game: &Game, elapsed: Duration) -> String {
+    let config = game.config();
+    let attempts = match game.status() {
+        GameStatus::Won => game.attempts().to_string(),
+        _ => String::from("X"),
+    };
+    let limit = match config.max_attempts {
+        Some(max_attempts) => format!("/{}", max_attempts),
+        None => String::new(),
+    };
+
+    let pattern: String = game
+        .history()
+        .iter()
+        .map(|&guess| match guess.cmp(&game.secret()) {
+            std::cmp::Ordering::Less => "⬆️",
+            std::cmp::Ordering::Greater => "⬇️",
+            std::cmp::Ordering::Equal => "✅",
+        })
+        .collect();
+
+    format!(
+        "{} ({}-{})\n{}{} in {}\n{}",
+        title,
+        config.min,
+        config.max,
+        attempts,
+        limit,
+        format_duration(elapsed),
+        pattern
+    )
+}
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::guess::Guess;
+use crate::secret::{Round, Secret, Turn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuessOutcome {
+    TooSmall,
+    TooBig,
+    Correct,
+    // The guess was wrong and it was the last one allowed,
+    // or the game was already over when the guess was made.
+    OutOfAttempts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameStatus {
+    InProgress,
+    Won,
+    Lost,
+}
+
+// The number the player is after, as a `Secret`.
+#[derive(Debug, Clone)]
+pub struct NumberSecret {
+    pub value: u32,
+    pub config: GameConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comparison(pub Ordering);
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Ordering::Less => write!(f, "Too small!"),
+            Ordering::Greater => write!(f, "Too big!"),
+            Ordering::Equal => write!(f, "You win!"),
+        }
+    }
+}
+
+impl Secret for NumberSecret {
+    type Guess = u32;
+    type Feedback = Comparison;
+
+    fn rules(&self) -> String {
+        format!(
+            "The secret number is between {} and {}.",
+            self.config.min, self.config.max
+        )
+    }
+
+    fn parse_guess(&self, input: &str) -> Result<u32, String> {
+        Guess::parse(input, &self.config)
+            .map(|guess| guess.value())
+            .map_err(|err| err.to_string())
+    }
+
+    fn check(&self, guess: &u32) -> Comparison {
+        Comparison(guess.cmp(&self.value))
+    }
+
+    fn is_solved(&self, feedback: &Comparison) -> bool {
+        feedback.0 == Ordering::Equal
+    }
+
+    fn reveal(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+// The number game is a round of `NumberSecret`, with a few shortcuts for
+// the number itself.
+pub type Game = Round<NumberSecret>;
+
+impl Game {
+    // The secret is drawn from `rng`, so passing a seeded generator
+    // such as `rand::rngs::StdRng::seed_from_u64` makes a game repeatable.
+    pub fn new<R: Rng + ?Sized>(config: GameConfig, rng: &mut R) -> Game {
+        let secret = rng.gen_range(config.min..=config.max);
+        Game::with_secret(config, secret)
+    }
+
+    // The same seed and config always give the same secret
+    // (as long as the `rand` version stays the same).
+    pub fn from_seed(config: GameConfig, seed: u64) -> Game {
+        Game::new(config, &mut StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_secret(config: GameConfig, secret: u32) -> Game {
+        Round::start(
+            NumberSecret {
+                value: secret,
+                config,
+            },
+            config.max_attempts,
+        )
+    }
+
+    pub fn guess(&mut self, guess: u32) -> GuessOutcome {
+        match self.take_turn(guess) {
+            Turn::Miss(Comparison(Ordering::Less)) => GuessOutcome::TooSmall,
+            Turn::Miss(_) => GuessOutcome::TooBig,
+            Turn::Solved(_) => GuessOutcome::Correct,
+            Turn::OutOfAttempts(_) | Turn::Over => GuessOutcome::OutOfAttempts,
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.puzzle().config
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.puzzle().value
+    }
+}
@@ -0,0 +1,100 @@
+// A guess typed by the player, checked before it reaches the game.
+//
+// The Guess type in the error handling chapter panics when it's given a
+// value out of range, which is right for a programming mistake but not for
+// a typo. Here every way the input can be wrong has its own `GuessError`,
+// so the player can be told exactly what to fix.
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::IntErrorKind;
+
+use crate::config::GameConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guess {
+    value: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    Empty,
+    NotANumber(String),
+    Negative(String),
+    Overflow(String),
+    OutOfRange { value: u32, min: u32, max: u32 },
+}
+
+impl Guess {
+    // Parses `input` and checks it against the range of `config`.
+    pub fn parse(input: &str, config: &GameConfig) -> Result<Guess, GuessError> {
+        Guess::try_from(input)?.within(config.min, config.max)
+    }
+
+    pub fn within(self, min: u32, max: u32) -> Result<Guess, GuessError> {
+        if self.value < min || self.value > max {
+            return Err(GuessError::OutOfRange {
+                value: self.value,
+                min,
+                max,
+            });
+        }
+
+        Ok(self)
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+// Only checks that the input is a whole number that fits in a `u32`;
+// use `Guess::parse` or `Guess::within` to check the game's range as well.
+impl TryFrom<&str> for Guess {
+    type Error = GuessError;
+
+    fn try_from(input: &str) -> Result<Guess, GuessError> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Err(GuessError::Empty);
+        }
+
+        match input.parse::<u32>() {
+            Ok(value) => Ok(Guess { value }),
+            Err(err) => {
+                let digits = input.strip_prefix('-').unwrap_or(input);
+                let is_integer = !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+
+                if is_integer && input.starts_with('-') {
+                    Err(GuessError::Negative(input.to_string()))
+                } else if *err.kind() == IntErrorKind::PosOverflow {
+                    Err(GuessError::Overflow(input.to_string()))
+                } else {
+                    Err(GuessError::NotANumber(input.to_string()))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessError::Empty => write!(f, "you didn't type anything, please enter a number"),
+            GuessError::NotANumber(input) => {
+                write!(f, "'{}' is not a number, please use digits only", input)
+            }
+            GuessError::Negative(input) => {
+                write!(f, "{} is negative, the secret number is never below zero", input)
+            }
+            GuessError::Overflow(input) => write!(f, "{} is far too big to be the secret", input),
+            GuessError::OutOfRange { value, min, max } => write!(
+                f,
+                "{} is out of range, the secret number is between {} and {}",
+                value, min, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
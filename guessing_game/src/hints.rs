@@ -0,0 +1,258 @@
+// Optional hints on top of "Too small!" and "Too big!".
+//
+// A hint is anything that implements `Hint`. Free hints (cost 0) speak up
+// after every guess; the others are bought with `hint <name>` and cost
+// attempts. New kinds of hint only need an implementation and an entry in
+// `by_name`, the game loop just asks the `HintSet` what to show.
+use std::fmt;
+
+use crate::game::Game;
+
+pub trait Hint {
+    // The name used to turn the hint on (`--hints warmer`) and to buy it.
+    fn name(&self) -> &'static str;
+
+    // How many attempts the hint costs. Free hints are shown automatically.
+    fn cost(&self) -> u32 {
+        0
+    }
+
+    // The clue for the game as it stands, if the hint has something to say.
+    fn clue(&mut self, game: &Game) -> Option<String>;
+}
+
+pub const NAMES: [&str; 4] = ["warmer", "bands", "parity", "divisible"];
+
+pub fn by_name(name: &str) -> Option<Box<dyn Hint>> {
+    match name {
+        "warmer" => Some(Box::new(WarmerColder)),
+        "bands" => Some(Box::new(DistanceBands)),
+        "parity" => Some(Box::new(Parity::default())),
+        "divisible" => Some(Box::new(Divisibility::new())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintError {
+    Unknown(String),
+    NotEnabled(String),
+    // Free hints are shown after each guess and can't be bought.
+    NotForSale(String),
+    NotEnoughAttempts { cost: u32 },
+    NothingNew(String),
+}
+
+impl fmt::Display for HintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HintError::Unknown(name) => write!(
+                f,
+                "there is no '{}' hint (try one of: {})",
+                name,
+                NAMES.join(", ")
+            ),
+            HintError::NotEnabled(name) => write!(f, "the '{}' hint isn't on in this game", name),
+            HintError::NotForSale(name) => {
+                write!(f, "the '{}' hint is free and shows up after every guess", name)
+            }
+            HintError::NotEnoughAttempts { cost } => write!(
+                f,
+                "that hint costs {} attempts and you need to keep at least one guess",
+                cost
+            ),
+            HintError::NothingNew(name) => write!(f, "the '{}' hint has nothing more to tell", name),
+        }
+    }
+}
+
+impl std::error::Error for HintError {}
+
+#[derive(Default)]
+pub struct HintSet {
+    hints: Vec<Box<dyn Hint>>,
+}
+
+impl HintSet {
+    pub fn new() -> HintSet {
+        HintSet::default()
+    }
+
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Result<HintSet, HintError> {
+        let mut set = HintSet::new();
+        for name in names {
+            let name = name.as_ref();
+            let hint = by_name(name).ok_or_else(|| HintError::Unknown(name.to_string()))?;
+            set.add(hint);
+        }
+        Ok(set)
+    }
+
+    pub fn add(&mut self, hint: Box<dyn Hint>) {
+        self.hints.push(hint);
+    }
+
+    // The clues from the free hints, to show after a guess.
+    pub fn after_guess(&mut self, game: &Game) -> Vec<String> {
+        self.hints
+            .iter_mut()
+            .filter(|hint| hint.cost() == 0)
+            .filter_map(|hint| hint.clue(game))
+            .collect()
+    }
+
+    // Names and costs of the hints that can be bought.
+    pub fn for_sale(&self) -> Vec<(&'static str, u32)> {
+        self.hints
+            .iter()
+            .filter(|hint| hint.cost() > 0)
+            .map(|hint| (hint.name(), hint.cost()))
+            .collect()
+    }
+
+    // Charges the hint's cost to `game` and returns its clue. Only the
+    // hints in this set can be bought, whether or not `by_name` knows them.
+    pub fn buy(&mut self, name: &str, game: &mut Game) -> Result<String, HintError> {
+        let hint = self
+            .hints
+            .iter_mut()
+            .find(|hint| hint.name() == name)
+            .ok_or_else(|| HintError::NotEnabled(name.to_string()))?;
+
+        let cost = hint.cost();
+        if cost == 0 {
+            return Err(HintError::NotForSale(name.to_string()));
+        }
+
+        if !game.can_spend(cost) {
+            return Err(HintError::NotEnoughAttempts { cost });
+        }
+
+        let clue = hint
+            .clue(game)
+            .ok_or_else(|| HintError::NothingNew(name.to_string()))?;
+        game.spend_attempts(cost);
+
+        Ok(clue)
+    }
+}
+
+// Says whether the last guess was closer to the secret than the one before.
+pub struct WarmerColder;
+
+impl Hint for WarmerColder {
+    fn name(&self) -> &'static str {
+        "warmer"
+    }
+
+    fn clue(&mut self, game: &Game) -> Option<String> {
+        let (previous, last) = match game.history() {
+            [.., previous, last] => (*previous, *last),
+            _ => return None,
+        };
+
+        if last == game.secret() {
+            return None;
+        }
+
+        let secret = game.secret();
+        let clue = match last.abs_diff(secret).cmp(&previous.abs_diff(secret)) {
+            std::cmp::Ordering::Less => "Warmer!",
+            std::cmp::Ordering::Greater => "Colder!",
+            std::cmp::Ordering::Equal => "Just as far as last time.",
+        };
+        Some(String::from(clue))
+    }
+}
+
+// Says roughly how far the last guess was from the secret.
+pub struct DistanceBands;
+
+impl DistanceBands {
+    const BANDS: [u32; 7] = [3, 5, 10, 25, 50, 100, 500];
+}
+
+impl Hint for DistanceBands {
+    fn name(&self) -> &'static str {
+        "bands"
+    }
+
+    fn clue(&mut self, game: &Game) -> Option<String> {
+        let last = *game.history().last()?;
+        let distance = last.abs_diff(game.secret());
+
+        if distance == 0 {
+            return None;
+        }
+
+        let clue = match DistanceBands::BANDS.iter().find(|&&band| distance <= band) {
+            Some(band) => format!("You're within {}.", band),
+            None => {
+                let widest = DistanceBands::BANDS[DistanceBands::BANDS.len() - 1];
+                format!("You're more than {} away.", widest)
+            }
+        };
+        Some(clue)
+    }
+}
+
+// Tells whether the secret is odd or even, once.
+#[derive(Default)]
+pub struct Parity {
+    told: bool,
+}
+
+impl Hint for Parity {
+    fn name(&self) -> &'static str {
+        "parity"
+    }
+
+    fn cost(&self) -> u32 {
+        1
+    }
+
+    fn clue(&mut self, game: &Game) -> Option<String> {
+        if self.told {
+            return None;
+        }
+        self.told = true;
+
+        let parity = if game.secret().is_multiple_of(2) { "even" } else { "odd" };
+        Some(format!("The secret number is {}.", parity))
+    }
+}
+
+// Each purchase tells whether the secret is divisible by the next divisor.
+pub struct Divisibility {
+    divisors: std::vec::IntoIter<u32>,
+}
+
+impl Divisibility {
+    pub fn new() -> Divisibility {
+        Divisibility {
+            divisors: vec![3, 5, 7, 4, 9, 11].into_iter(),
+        }
+    }
+}
+
+impl Default for Divisibility {
+    fn default() -> Divisibility {
+        Divisibility::new()
+    }
+}
+
+impl Hint for Divisibility {
+    fn name(&self) -> &'static str {
+        "divisible"
+    }
+
+    fn cost(&self) -> u32 {
+        2
+    }
+
+    fn clue(&mut self, game: &Game) -> Option<String> {
+        let divisor = self.divisors.next()?;
+        let not = if game.secret().is_multiple_of(divisor) { "" } else { "not " };
+        Some(format!("The secret number is {}divisible by {}.", not, divisor))
+    }
+}
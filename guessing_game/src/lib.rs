@@ -0,0 +1,23 @@
+// The guessing game engine.
+//
+// Nothing in here reads from stdin or prints to stdout, so the same
+// `Game` can be driven by the command line in `main.rs`, by other
+// frontends, or by a bot playing with a seeded random number generator.
+pub mod bench;
+pub mod config;
+pub mod daily;
+pub mod date;
+pub mod game;
+pub mod guess;
+pub mod hints;
+pub mod mastermind;
+pub mod reverse;
+pub mod scores;
+pub mod script;
+pub mod secret;
+pub mod server;
+pub mod wordle;
+
+pub use config::{Difficulty, GameConfig, Mode, Options};
+pub use game::{Game, GameStatus, GuessOutcome};
+pub use guess::{Guess, GuessError};
@@ -1,36 +1,625 @@
-use std::io;
-use rand::Rng;
-use std::cmp::Ordering;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, IsTerminal};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use guessing_game::bench::{self, Strategy};
+use guessing_game::daily;
+use guessing_game::date::Date;
+use guessing_game::game::{Comparison, NumberSecret};
+use guessing_game::hints::HintSet;
+use guessing_game::mastermind::{Code, Mastermind};
+use guessing_game::reverse::{self, Answer, Solver};
+use guessing_game::scores::{self, Score, ScoreTable};
+use guessing_game::script;
+use guessing_game::secret::{Round, Secret, Turn};
+use guessing_game::server;
+use guessing_game::wordle::Wordle;
+mod screen;
+
+use screen::{LineScreen, Screen, TuiScreen};
+
+use guessing_game::{Game, GameConfig, Mode, Options};
 
 fn main() {
-    println!("Guess the number!");
+    let options = Options::build(env::args()).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!(
+            "Usage: guessing_game [bench|mastermind|wordle] [--difficulty easy|normal|hard] [--min N] [--max N] [--attempts N]\n\
+             \x20                    [--name NAME] [--scores] [--scores-file PATH] [--reverse]\n\
+             \x20                    [--serve PORT] [--connect HOST:PORT]\n\
+             \x20                    [--seed N] [--input GUESSES] [--replay TRANSCRIPT]\n\
+             \x20                    [--hints warmer,bands,parity,divisible]\n\
+             \x20                    [--games N] [--strategy binary,golden,random,linear] [--tui]\n\
+             \x20                    [--daily] [--time-attack]"
+        );
+        process::exit(1);
+    });
+
+    let scores_file = options.scores_file.clone().unwrap_or_else(scores::default_path);
+
+    match options.mode {
+        Mode::Play => {
+            let hints = HintSet::from_names(&options.hints).unwrap_or_else(|err| {
+                eprintln!("Problem parsing arguments: {}", err);
+                process::exit(1);
+            });
+
+            // The full-screen view needs a terminal on both ends;
+            // anything else gets the plain line-by-line game.
+            let mut screen: Box<dyn Screen> =
+                if options.tui && io::stdout().is_terminal() && io::stdin().is_terminal() {
+                    Box::new(TuiScreen::new(Instant::now()))
+                } else {
+                    Box::new(LineScreen::default())
+                };
+
+            if let Some(score) = play(&options, hints, screen.as_mut()) {
+                record_score(&scores_file, score);
+            }
+        }
+        Mode::Scores => print_scores(&scores_file),
+        Mode::Reverse => play_reverse(options.game),
+        Mode::Serve(port) => host(options.game, port),
+        Mode::Connect(address) => join(&address),
+        Mode::Script(path) => run_script(options.game, options.seed, &path),
+        Mode::Replay(path) => run_replay(&path),
+        Mode::Bench => run_bench(&options),
+        Mode::Mastermind => {
+            let code = Code::random(&mut rng(options.seed));
+            play_round(&mut Round::start(Mastermind::new(code), options.game.max_attempts), &mut Plain);
+        }
+        Mode::Wordle => {
+            let wordle = Wordle::random(&mut rng(options.seed));
+            play_round(&mut Round::start(wordle, options.game.max_attempts), &mut Plain);
+        }
+    }
+}
+
+// Plays one round on stdin and returns the score if the player won.
+fn play(options: &Options, hints: HintSet, screen: &mut dyn Screen) -> Option<Score> {
+    let config = options.game;
+    let today = Date::today();
+    let mut game = match options.seed {
+        _ if options.daily => daily::game(today, config),
+        Some(seed) => Game::from_seed(config, seed),
+        None => Game::new(config, &mut rand::thread_rng()),
+    };
+
+    let mut hooks = NumberHooks {
+        options,
+        hints,
+        screen,
+        today,
+        started: Instant::now(),
+        score: None,
+    };
+    play_round(&mut game, &mut hooks);
+    hooks.score
+}
+
+// What the number game adds to the shared loop: its own screen, hints,
+// the clock for time attack, and a score when the player wins.
+struct NumberHooks<'a> {
+    options: &'a Options,
+    hints: HintSet,
+    screen: &'a mut dyn Screen,
+    today: Date,
+    started: Instant,
+    score: Option<Score>,
+}
+
+impl NumberHooks<'_> {
+    fn say_guess(&mut self, game: &Game) {
+        if let Some(guess) = game.history().last() {
+            self.screen.say(&format!("You guessed: {}", guess));
+        }
+    }
+}
+
+impl Hooks<NumberSecret> for NumberHooks<'_> {
+    fn say(&mut self, message: &str) {
+        self.screen.say(message);
+    }
+
+    fn introduce(&mut self, game: &Game) {
+        self.say("Guess the number!");
+        self.say(&game.puzzle().rules());
+        if let Some(attempts_left) = game.attempts_left() {
+            self.say(&format!("You have {} attempts.", attempts_left));
+        }
+
+        say_hints_for_sale(&self.hints, self.screen);
+        if self.options.daily {
+            self.say(&format!("Today's puzzle: {}", self.today));
+        }
+        if self.options.time_attack {
+            self.say("Time attack! The clock starts now.");
+        }
+        self.started = Instant::now();
+    }
+
+    fn prompt(&mut self, game: &Game) {
+        self.screen.prompt(game);
+    }
+
+    fn command(&mut self, game: &mut Game, input: &str) -> bool {
+        let mut words = input.split_whitespace();
+        if !words.next().is_some_and(|word| word.eq_ignore_ascii_case("hint")) {
+            return false;
+        }
+
+        match words.next() {
+            Some(name) => match self.hints.buy(&name.to_lowercase(), game) {
+                Ok(clue) => self.say(&clue),
+                Err(err) => self.say(&format!("Oops: {}.", err)),
+            },
+            None => say_hints_for_sale(&self.hints, self.screen),
+        }
+        true
+    }
+
+    fn missed(&mut self, game: &Game, feedback: &Comparison) {
+        self.say_guess(game);
+        self.say(&feedback.to_string());
+
+        for clue in self.hints.after_guess(game) {
+            self.say(&clue);
+        }
+
+        if self.options.time_attack {
+            let time = scores::format_duration(self.started.elapsed());
+            self.say(&format!("Time: {}", time));
+        }
+    }
+
+    fn won(&mut self, game: &Game, feedback: &Comparison) {
+        self.say_guess(game);
+        self.say(&feedback.to_string());
+
+        let duration = self.started.elapsed();
+        self.say(&format!(
+            "You found it in {} attempts ({}).",
+            game.attempts(),
+            scores::format_duration(duration)
+        ));
+        share(self.options, self.today, game, duration, self.screen);
+        self.screen.finish(game);
+
+        let config = game.config();
+        self.score = Some(Score {
+            name: self.options.player.clone().unwrap_or_else(ask_name),
+            attempts: game.attempts(),
+            min: config.min,
+            max: config.max,
+            duration_ms: duration.as_millis() as u64,
+            date: self.today.to_string(),
+            time_attack: self.options.time_attack,
+        });
+    }
+
+    fn lost(&mut self, game: &Game, _feedback: &Comparison) {
+        self.say_guess(game);
+        self.say(&format!("Out of attempts! The secret number was {}.", game.secret()));
+        share(self.options, self.today, game, self.started.elapsed(), self.screen);
+        self.screen.finish(game);
+    }
+
+    fn no_more_input(&mut self, game: &Game) {
+        self.say(&format!("No more input. The secret number was {}.", game.secret()));
+        self.screen.finish(game);
+    }
+}
+
+// Daily and time attack games end with a summary that can be pasted into
+// a chat without spoiling the number. `today` is the day the puzzle was
+// made for, even if the game ran past midnight.
+fn share(options: &Options, today: Date, game: &Game, elapsed: Duration, screen: &mut dyn Screen) {
+    let title = match (options.daily, options.time_attack) {
+        (true, true) => format!("Guessing game daily {} time attack", today),
+        (true, false) => format!("Guessing game daily {}", today),
+        (false, true) => String::from("Guessing game time attack"),
+        (false, false) => return,
+    };
+
+    screen.say("");
+    for line in daily::share_summary(&title, game, elapsed).lines() {
+        screen.say(line);
+    }
+}
+
+fn say_hints_for_sale(hints: &HintSet, screen: &mut dyn Screen) {
+    let for_sale = hints.for_sale();
+    if for_sale.is_empty() {
+        return;
+    }
+
+    let prices: Vec<String> = for_sale
+        .iter()
+        .map(|(name, cost)| format!("'hint {}' costs {}", name, cost))
+        .collect();
+    screen.say(&format!("Hints for sale (in attempts): {}", prices.join(", ")));
+}
+
+fn ask_name() -> String {
+    println!("Enter your name for the high-score table:");
+
+    let mut name = String::new();
+
+    // A closed stdin just means we record the score anonymously.
+    if io::stdin().read_line(&mut name).is_err() || name.trim().is_empty() {
+        return String::from("anonymous");
+    }
+
+    String::from(name.trim())
+}
+
+fn record_score(path: &Path, score: Score) {
+    let (mut table, err) = ScoreTable::load_or_empty(path);
+    if let Some(err) = err {
+        eprintln!("Warning: {}", err);
+    }
+
+    table.add(score);
+
+    if let Err(err) = table.save() {
+        eprintln!("Warning: your score was not saved: {}", err);
+    }
+}
+
+fn print_scores(path: &Path) {
+    let (table, err) = ScoreTable::load_or_empty(path);
+    if let Some(err) = err {
+        eprintln!("Warning: {}", err);
+    }
+
+    let leaderboard = table.leaderboard();
+    let time_attack = table.time_attack_leaderboard();
+
+    if leaderboard.is_empty() && time_attack.is_empty() {
+        println!("No high scores yet in {}.", table.path().display());
+        return;
+    }
+
+    if !leaderboard.is_empty() {
+        print_leaderboard(&leaderboard);
+    }
+
+    if !time_attack.is_empty() {
+        if !leaderboard.is_empty() {
+            println!();
+        }
+        println!("Time attack");
+        print_leaderboard(&time_attack);
+    }
+}
 
-    let secret_number = rand::thread_rng().gen_range(1..101);
+fn print_leaderboard(leaderboard: &[&Score]) {
+    println!("Rank  Name             Attempts  Range           Time  Date");
+
+    for (rank, score) in leaderboard.iter().enumerate() {
+        println!(
+            "{:>4}  {:<16} {:>8}  {:<11} {:>8}  {}",
+            rank + 1,
+            score.name,
+            score.attempts,
+            format!("{}-{}", score.min, score.max),
+            scores::format_duration(score.duration()),
+            score.date
+        );
+    }
+}
+
+// The player picks the number and the computer searches for it.
+fn play_reverse(config: GameConfig) {
+    println!(
+        "Think of a number between {} and {} and I'll guess it.",
+        config.min, config.max
+    );
+    println!("Answer each guess with higher, lower or correct (h/l/c).");
+
+    let mut solver = Solver::new(config.min, config.max);
 
     loop {
-    
-        println!("Please input your guess.");
+        let guess = solver.next_guess();
+        println!("Is it {}?", guess);
 
-        let mut guess = String::new();
+        let answer = loop {
+            let mut answer = String::new();
 
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
+            let read = io::stdin()
+                .read_line(&mut answer)
+                .expect("Failed to read line");
 
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
+            if read == 0 {
+                println!("No answer given, giving up.");
+                return;
+            }
+
+            match Answer::parse(&answer) {
+                Some(answer) => break answer,
+                None => println!("Please answer higher, lower or correct (h/l/c)."),
+            }
         };
 
-        println!("You guessed: {}", guess);
+        match solver.answer(answer) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(cheating) => {
+                println!("Hold on, that's not possible: {}.", cheating);
+                return;
+            }
+        }
+    }
 
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
-            },
+    let bound = reverse::guess_bound(config.min, config.max);
+    let candidates = f64::from(config.max - config.min) + 1.0;
+
+    println!("Got it in {} guesses!", solver.guesses());
+    println!(
+        "Binary search never needs more than {} guesses for {} numbers (log2 {} = {:.2}).",
+        bound,
+        candidates,
+        candidates,
+        candidates.log2()
+    );
+}
+
+fn host(config: GameConfig, port: u16) {
+    let listener = TcpListener::bind(("0.0.0.0", port)).unwrap_or_else(|err| {
+        eprintln!("Could not listen on port {}: {}", port, err);
+        process::exit(1);
+    });
+
+    println!(
+        "Serving a game between {} and {} on port {}. Players can join with:",
+        config.min, config.max, port
+    );
+    println!("    guessing_game --connect localhost:{}", port);
+
+    let game = Game::new(config, &mut rand::thread_rng());
+
+    match server::serve(listener, game) {
+        Ok(standings) => {
+            println!("Game over!");
+            for (rank, standing) in standings.iter().enumerate() {
+                let marker = if standing.winner { " (winner)" } else { "" };
+                println!(
+                    "{:>3}. {} - {} guesses{}",
+                    rank + 1,
+                    standing.name,
+                    standing.attempts,
+                    marker
+                );
+            }
+        }
+        Err(err) => {
+            eprintln!("The server stopped: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn join(address: &str) {
+    println!("Connecting to {}. Type NAME <name> to pick a name, then a number on your turn.", address);
+
+    let result = server::connect(address, io::stdin().lock(), |line| {
+        println!("{}", line);
+        // The server closes the game after the scoreboard, so there's
+        // no point waiting for the player to type another line.
+        if line == "END" {
+            process::exit(0);
+        }
+    });
+
+    if let Err(err) = result {
+        eprintln!("Connection to {} failed: {}", address, err);
+        process::exit(1);
+    }
+}
+
+// Plays a file of guesses and prints the transcript as JSON lines.
+fn run_script(config: GameConfig, seed: Option<u64>, path: &Path) {
+    // Without a seed we still pick one, so the transcript can be replayed.
+    let seed = seed.unwrap_or_else(rand::random);
+
+    let records = File::open(path)
+        .and_then(|file| script::run(config, seed, BufReader::new(file)))
+        .unwrap_or_else(|err| {
+            eprintln!("Could not read guesses from {}: {}", path.display(), err);
+            process::exit(1);
+        });
+
+    for record in records {
+        println!(
+            "{}",
+            serde_json::to_string(&record).expect("a record always serializes")
+        );
+    }
+}
+
+fn run_replay(path: &Path) {
+    let result = File::open(path)
+        .map_err(script::ReplayError::from)
+        .and_then(|file| script::read_transcript(BufReader::new(file)))
+        .and_then(|records| script::replay(&records));
+
+    match result {
+        Ok(attempts) => println!("Replay of {} matches ({} attempts).", path.display(), attempts),
+        Err(err) => {
+            eprintln!("Replay of {} failed: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_bench(options: &Options) {
+    let names: Vec<&str> = if options.strategies.is_empty() {
+        bench::NAMES.to_vec()
+    } else {
+        options.strategies.iter().map(String::as_str).collect()
+    };
+
+    let mut strategies: Vec<Box<dyn Strategy>> = names
+        .iter()
+        .map(|name| {
+            bench::by_name(name).unwrap_or_else(|| {
+                eprintln!(
+                    "Unknown strategy '{}' (expected one of: {})",
+                    name,
+                    bench::NAMES.join(", ")
+                );
+                process::exit(1);
+            })
+        })
+        .collect();
+
+    let config = options.game;
+    let seed = options.seed.unwrap_or(0);
+    let reports = bench::run(config, options.games, seed, &mut strategies);
+
+    println!(
+        "{} games between {} and {} (seed {}), log2 bound {} guesses",
+        options.games,
+        config.min,
+        config.max,
+        seed,
+        reverse::guess_bound(config.min, config.max)
+    );
+    println!();
+    println!("Strategy      Mean  Median   Max  Unsolved");
+
+    for report in &reports {
+        println!(
+            "{:<8} {:>9.2} {:>7.1} {:>5} {:>9}",
+            report.strategy,
+            report.mean(),
+            report.median(),
+            report.max(),
+            report.unsolved
+        );
+    }
+
+    for report in &reports {
+        println!();
+        println!("{}: games by attempts", report.strategy);
+
+        let histogram = report.histogram(20);
+        let most = histogram.iter().map(|&(_, _, games)| games).max().unwrap_or(1);
+
+        for (low, high, games) in histogram {
+            let label = if low == high {
+                low.to_string()
+            } else {
+                format!("{}-{}", low, high)
+            };
+            let bar = "#".repeat((games * 40).div_ceil(most));
+            println!("{:>9} | {:<40} {}", label, bar, games);
+        }
+    }
+}
+
+fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+// What a kind of game adds to the shared loop in `play_round`. The
+// defaults just print, which is all Mastermind and word guessing need.
+trait Hooks<S: Secret> {
+    fn say(&mut self, message: &str) {
+        println!("{}", message);
+    }
+
+    // Called once before the first guess.
+    fn introduce(&mut self, round: &Round<S>) {
+        self.say(&round.puzzle().rules());
+        if let Some(attempts_left) = round.attempts_left() {
+            self.say(&format!("You have {} attempts.", attempts_left));
+        }
+    }
+
+    // Called before each line of input is read.
+    fn prompt(&mut self, _round: &Round<S>) {
+        self.say("Please input your guess.");
+    }
+
+    // Takes a line that isn't a guess, such as `hint parity`. Returns
+    // whether it did.
+    fn command(&mut self, _round: &mut Round<S>, _input: &str) -> bool {
+        false
+    }
+
+    fn missed(&mut self, round: &Round<S>, feedback: &S::Feedback) {
+        self.say(&feedback.to_string());
+        if let Some(attempts_left) = round.attempts_left() {
+            self.say(&format!("Attempts left: {}", attempts_left));
+        }
+    }
+
+    fn won(&mut self, round: &Round<S>, feedback: &S::Feedback) {
+        self.say(&feedback.to_string());
+        self.say(&format!("You win in {} attempts!", round.attempts()));
+    }
+
+    fn lost(&mut self, round: &Round<S>, feedback: &S::Feedback) {
+        self.say(&feedback.to_string());
+        self.say(&format!("Out of attempts! The answer was {}.", round.puzzle().reveal()));
+    }
+
+    fn no_more_input(&mut self, round: &Round<S>) {
+        self.say(&format!("No more input. The answer was {}.", round.puzzle().reveal()));
+    }
+}
+
+// Hooks that leave everything to the defaults.
+struct Plain;
+
+impl<S: Secret> Hooks<S> for Plain {}
+
+// The game loop for any kind of `Secret`.
+fn play_round<S: Secret>(round: &mut Round<S>, hooks: &mut impl Hooks<S>) {
+    hooks.introduce(round);
+
+    loop {
+        hooks.prompt(round);
+
+        let mut input = String::new();
+
+        let read = io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+
+        if read == 0 {
+            hooks.no_more_input(round);
+            return;
+        }
+
+        if hooks.command(round, &input) {
+            continue;
+        }
+
+        // Mistakes in the input don't cost an attempt.
+        match round.play(&input) {
+            Ok(Turn::Miss(feedback)) => hooks.missed(round, &feedback),
+            Ok(Turn::Solved(feedback)) => {
+                hooks.won(round, &feedback);
+                return;
+            }
+            Ok(Turn::OutOfAttempts(feedback)) => {
+                hooks.lost(round, &feedback);
+                return;
+            }
+            Ok(Turn::Over) => return,
+            Err(err) => hooks.say(&format!("Oops: {}.", err)),
         }
     }
 }
@@ -0,0 +1,124 @@
+// Mastermind: a hidden code of four pegs in six colors (repeats allowed).
+// Each guess is scored with a black peg for every peg of the right color
+// in the right place, and a white peg for every other right color.
+use std::fmt;
+
+use rand::Rng;
+
+use crate::secret::Secret;
+
+pub const COLORS: [char; 6] = ['R', 'O', 'Y', 'G', 'B', 'P'];
+pub const PEGS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Code(pub [char; PEGS]);
+
+impl Code {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Code {
+        let mut pegs = [COLORS[0]; PEGS];
+        for peg in pegs.iter_mut() {
+            *peg = COLORS[rng.gen_range(0..COLORS.len())];
+        }
+        Code(pegs)
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.iter().collect::<String>())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pegs {
+    pub black: usize,
+    pub white: usize,
+}
+
+impl fmt::Display for Pegs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pegs = "●".repeat(self.black) + &"○".repeat(self.white);
+        let pegs = if pegs.is_empty() { String::from("-") } else { pegs };
+        write!(
+            f,
+            "{:<4}  ({} black, {} white)",
+            pegs, self.black, self.white
+        )
+    }
+}
+
+pub struct Mastermind {
+    code: Code,
+}
+
+impl Mastermind {
+    pub fn new(code: Code) -> Mastermind {
+        Mastermind { code }
+    }
+}
+
+impl Secret for Mastermind {
+    type Guess = Code;
+    type Feedback = Pegs;
+
+    fn rules(&self) -> String {
+        format!(
+            "Crack the code of {} pegs. Colors: {} (e.g. RGBY).\n\
+             ● right color in the right place, ○ right color in the wrong place.",
+            PEGS,
+            COLORS.iter().collect::<String>()
+        )
+    }
+
+    fn parse_guess(&self, input: &str) -> Result<Code, String> {
+        let pegs: Vec<char> = input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        if pegs.len() != PEGS {
+            return Err(format!("a code has {} pegs, you gave {}", PEGS, pegs.len()));
+        }
+
+        if let Some(bad) = pegs.iter().find(|c| !COLORS.contains(c)) {
+            return Err(format!(
+                "'{}' is not a color, use {}",
+                bad,
+                COLORS.iter().collect::<String>()
+            ));
+        }
+
+        let mut code = [COLORS[0]; PEGS];
+        code.copy_from_slice(&pegs);
+        Ok(Code(code))
+    }
+
+    fn check(&self, guess: &Code) -> Pegs {
+        let black = (0..PEGS).filter(|&i| guess.0[i] == self.code.0[i]).count();
+
+        // Every color counts as many times as it appears in both codes;
+        // whatever isn't already a black peg is a white one.
+        let matches: usize = COLORS
+            .iter()
+            .map(|color| {
+                let in_guess = guess.0.iter().filter(|&c| c == color).count();
+                let in_code = self.code.0.iter().filter(|&c| c == color).count();
+                in_guess.min(in_code)
+            })
+            .sum();
+
+        Pegs {
+            black,
+            white: matches - black,
+        }
+    }
+
+    fn is_solved(&self, feedback: &Pegs) -> bool {
+        feedback.black == PEGS
+    }
+
+    fn reveal(&self) -> String {
+        self.code.to_string()
+    }
+}
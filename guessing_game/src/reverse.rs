@@ -0,0 +1,116 @@
+// Reverse mode: the player thinks of a number and the computer finds it
+// with a binary search, halving the remaining range after every answer.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    // The secret is higher than the guess.
+    Higher,
+    // The secret is lower than the guess.
+    Lower,
+    Correct,
+}
+
+impl Answer {
+    pub fn parse(input: &str) -> Option<Answer> {
+        match input.trim().to_lowercase().as_str() {
+            "h" | "higher" | "bigger" | "too small" => Some(Answer::Higher),
+            "l" | "lower" | "smaller" | "too big" => Some(Answer::Lower),
+            "c" | "correct" | "yes" | "y" => Some(Answer::Correct),
+            _ => None,
+        }
+    }
+}
+
+// Returned when the answers so far can't all be true for any number
+// in the range, e.g. "higher than 50" followed by "lower than 51".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cheating {
+    pub guess: u32,
+    pub answer: Answer,
+    pub low: u32,
+    pub high: u32,
+}
+
+impl fmt::Display for Cheating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = match self.answer {
+            Answer::Higher => "higher",
+            Answer::Lower => "lower",
+            Answer::Correct => "equal",
+        };
+        write!(
+            f,
+            "your earlier answers only left {}..={}, so the number can't be {} than {}",
+            self.low, self.high, direction, self.guess
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Solver {
+    // The secret is known to be in `low..=high`.
+    low: u32,
+    high: u32,
+    guesses: u32,
+    last_guess: Option<u32>,
+}
+
+impl Solver {
+    pub fn new(min: u32, max: u32) -> Solver {
+        Solver {
+            low: min,
+            high: max,
+            guesses: 0,
+            last_guess: None,
+        }
+    }
+
+    pub fn next_guess(&mut self) -> u32 {
+        // Written this way so `low + high` can't overflow near `u32::MAX`.
+        let guess = self.low + (self.high - self.low) / 2;
+        self.guesses += 1;
+        self.last_guess = Some(guess);
+        guess
+    }
+
+    // Narrows the range with the answer to the last guess.
+    // Returns `Ok(true)` once the number has been found.
+    pub fn answer(&mut self, answer: Answer) -> Result<bool, Cheating> {
+        let guess = self
+            .last_guess
+            .expect("`answer` is only called after `next_guess`");
+
+        let cheating = Cheating {
+            guess,
+            answer,
+            low: self.low,
+            high: self.high,
+        };
+
+        match answer {
+            Answer::Correct => return Ok(true),
+            Answer::Higher if guess >= self.high => return Err(cheating),
+            Answer::Lower if guess <= self.low => return Err(cheating),
+            Answer::Higher => self.low = guess + 1,
+            Answer::Lower => self.high = guess - 1,
+        }
+
+        Ok(false)
+    }
+
+    pub fn guesses(&self) -> u32 {
+        self.guesses
+    }
+
+    pub fn range(&self) -> (u32, u32) {
+        (self.low, self.high)
+    }
+}
+
+// The most guesses a binary search can need for a range of `min..=max`,
+// which is ceil(log2(n + 1)) for `n` candidate numbers.
+pub fn guess_bound(min: u32, max: u32) -> u32 {
+    let candidates = u64::from(max - min) + 1;
+    64 - candidates.leading_zeros()
+}
@@ -0,0 +1,190 @@
+// The high-score table, kept as a JSON file in the user's data directory:
+//
+//     Linux:   $XDG_DATA_HOME/guessing_game/scores.json
+//              (or ~/.local/share/guessing_game/scores.json)
+//     macOS:   ~/Library/Application Support/guessing_game/scores.json
+//     Windows: %APPDATA%\guessing_game\scores.json
+//
+// A file that can't be read back as a score table is moved aside to
+// `scores.json.corrupt` instead of crashing the game, and a fresh table
+// is started in its place.
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    pub attempts: u32,
+    pub min: u32,
+    pub max: u32,
+    pub duration_ms: u64,
+    // Stored as YYYY-MM-DD so the file stays readable by hand.
+    pub date: String,
+    // Time attack games are ranked by time alone, on their own board.
+    // Older files don't have the field, so it defaults to false.
+    #[serde(default)]
+    pub time_attack: bool,
+}
+
+impl Score {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+#[derive(Debug)]
+pub enum ScoreError {
+    Io(io::Error),
+    // The file was there but didn't hold a score table.
+    // It has already been renamed to `backup` when this is returned.
+    Corrupt {
+        backup: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreError::Io(err) => write!(f, "could not access the high-score file: {}", err),
+            ScoreError::Corrupt { backup, source } => write!(
+                f,
+                "the high-score file was unreadable ({}); it was moved to {} and a new table was started",
+                source,
+                backup.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScoreError {}
+
+impl From<io::Error> for ScoreError {
+    fn from(err: io::Error) -> ScoreError {
+        ScoreError::Io(err)
+    }
+}
+
+#[derive(Debug)]
+pub struct ScoreTable {
+    path: PathBuf,
+    scores: Vec<Score>,
+}
+
+impl ScoreTable {
+    pub fn empty(path: &Path) -> ScoreTable {
+        ScoreTable {
+            path: path.to_path_buf(),
+            scores: Vec::new(),
+        }
+    }
+
+    // A missing file is not an error: nobody has won a game yet.
+    pub fn load(path: &Path) -> Result<ScoreTable, ScoreError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(ScoreTable::empty(path))
+            }
+            Err(err) => return Err(ScoreError::Io(err)),
+        };
+
+        match serde_json::from_str(&contents) {
+            Ok(scores) => Ok(ScoreTable {
+                path: path.to_path_buf(),
+                scores,
+            }),
+            Err(source) => {
+                let backup = path.with_extension("json.corrupt");
+                fs::rename(path, &backup)?;
+                Err(ScoreError::Corrupt { backup, source })
+            }
+        }
+    }
+
+    // Loads the table, falling back to an empty one if the file is unreadable.
+    // The error is handed back so the caller can tell the player about it.
+    pub fn load_or_empty(path: &Path) -> (ScoreTable, Option<ScoreError>) {
+        match ScoreTable::load(path) {
+            Ok(table) => (table, None),
+            Err(err) => (ScoreTable::empty(path), Some(err)),
+        }
+    }
+
+    pub fn add(&mut self, score: Score) {
+        self.scores.push(score);
+    }
+
+    // The file is written next to its final location first and then renamed
+    // over it, so a crash halfway through never leaves a truncated table.
+    pub fn save(&self) -> Result<(), ScoreError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let json = serde_json::to_string_pretty(&self.scores)
+            .expect("a score table always serializes");
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+
+    // Fewest attempts first; ties go to the faster game.
+    pub fn leaderboard(&self) -> Vec<&Score> {
+        let mut scores: Vec<&Score> = self.scores.iter().filter(|s| !s.time_attack).collect();
+        scores.sort_by_key(|score| (score.attempts, score.duration_ms));
+        scores
+    }
+
+    // Fastest first; ties go to the game with fewer attempts.
+    pub fn time_attack_leaderboard(&self) -> Vec<&Score> {
+        let mut scores: Vec<&Score> = self.scores.iter().filter(|s| s.time_attack).collect();
+        scores.sort_by_key(|score| (score.duration_ms, score.attempts));
+        scores
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+pub fn default_path() -> PathBuf {
+    data_dir().join("guessing_game").join("scores.json")
+}
+
+fn data_dir() -> PathBuf {
+    if cfg!(windows) {
+        if let Some(appdata) = env::var_os("APPDATA") {
+            return PathBuf::from(appdata);
+        }
+    } else if let Some(xdg) = env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(xdg);
+    } else if let Some(home) = env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        return if cfg!(target_os = "macos") {
+            home.join("Library").join("Application Support")
+        } else {
+            home.join(".local").join("share")
+        };
+    }
+
+    PathBuf::from(".")
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}:{:02}.{}",
+        seconds / 60,
+        seconds % 60,
+        duration.subsec_millis() / 100
+    )
+}
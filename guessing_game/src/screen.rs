@@ -0,0 +1,222 @@
+// How a round of the game is shown to the player.
+//
+// `LineScreen` is the plain println version that works anywhere, including
+// pipes and scripts. `TuiScreen` redraws a full-screen view in place with
+// ANSI escape codes, and is only used when stdout is a terminal.
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use guessing_game::Game;
+
+pub trait Screen {
+    // Called before each line of input is read.
+    fn prompt(&mut self, game: &Game);
+
+    // One line of feedback for the player.
+    fn say(&mut self, message: &str);
+
+    // Called once when the round is over, won or not.
+    fn finish(&mut self, _game: &Game) {}
+}
+
+#[derive(Default)]
+pub struct LineScreen {
+    // Only repeat "Attempts left" when it has changed.
+    shown_attempts: u32,
+}
+
+impl Screen for LineScreen {
+    fn prompt(&mut self, game: &Game) {
+        if game.attempts() != self.shown_attempts {
+            self.shown_attempts = game.attempts();
+            if let Some(attempts_left) = game.attempts_left() {
+                println!("Attempts left: {}", attempts_left);
+            }
+        }
+
+        println!("Please input your guess.");
+    }
+
+    fn say(&mut self, message: &str) {
+        println!("{}", message);
+    }
+}
+
+const WIDTH: usize = 60;
+const BAR: usize = 40;
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+pub struct TuiScreen {
+    started: Instant,
+    messages: Vec<String>,
+    stop: Arc<AtomicBool>,
+    timer: Option<JoinHandle<()>>,
+}
+
+impl TuiScreen {
+    pub fn new(started: Instant) -> TuiScreen {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // Ticks the clock in the top right corner while the player types.
+        // Saving and restoring the cursor around the write keeps whatever
+        // they're typing where it was.
+        let timer = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(250));
+                    let mut stdout = io::stdout().lock();
+                    let _ = write!(
+                        stdout,
+                        "\x1b7\x1b[1;{}H{}{}\x1b8",
+                        WIDTH - 5,
+                        clock(started.elapsed()),
+                        RESET
+                    );
+                    let _ = stdout.flush();
+                }
+            })
+        };
+
+        TuiScreen {
+            started,
+            messages: Vec::new(),
+            stop,
+            timer: Some(timer),
+        }
+    }
+
+    fn draw(&mut self, game: &Game, finished: bool) {
+        let config = game.config();
+        let (low, high) = remaining_range(game);
+        let mut frame = String::from(CLEAR);
+
+        frame.push_str(&format!(
+            "{}{:<w$}{}{:>6}\n",
+            BOLD,
+            "  GUESS THE NUMBER",
+            RESET,
+            clock(self.started.elapsed()),
+            w = WIDTH - 6
+        ));
+        frame.push_str(&format!("  {}\n\n", "─".repeat(WIDTH - 2)));
+
+        frame.push_str(&format!(
+            "  {} [{}] {}\n",
+            config.min,
+            range_bar(config.min, config.max, low, high),
+            config.max
+        ));
+        frame.push_str(&format!("  {}still possible: {} - {}{}\n\n", DIM, low, high, RESET));
+
+        match game.attempts_left() {
+            Some(left) => frame.push_str(&format!(
+                "  Attempts left: {}{}{} of {}\n",
+                BOLD,
+                left,
+                RESET,
+                config.max_attempts.unwrap_or(0)
+            )),
+            None => frame.push_str(&format!("  Attempts used: {}\n", game.attempts())),
+        }
+
+        let history: Vec<String> = game
+            .history()
+            .iter()
+            .map(|&guess| match guess.cmp(&game.secret()) {
+                std::cmp::Ordering::Less => format!("{}↑", guess),
+                std::cmp::Ordering::Greater => format!("{}↓", guess),
+                std::cmp::Ordering::Equal => format!("{}✓", guess),
+            })
+            .collect();
+        frame.push_str(&format!("  Guesses: {}\n", history.join("  ")));
+        frame.push_str(&format!("  {}\n", "─".repeat(WIDTH - 2)));
+
+        for message in self.messages.drain(..) {
+            frame.push_str(&format!("  {}\n", message));
+        }
+
+        if !finished {
+            frame.push_str("\n  Your guess (or 'hint <name>'): ");
+        }
+
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(frame.as_bytes());
+        let _ = stdout.flush();
+    }
+
+    fn stop_timer(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(timer) = self.timer.take() {
+            let _ = timer.join();
+        }
+    }
+}
+
+impl Screen for TuiScreen {
+    fn prompt(&mut self, game: &Game) {
+        self.draw(game, false);
+    }
+
+    fn say(&mut self, message: &str) {
+        self.messages.push(message.to_string());
+    }
+
+    fn finish(&mut self, game: &Game) {
+        self.stop_timer();
+        self.draw(game, true);
+    }
+}
+
+impl Drop for TuiScreen {
+    fn drop(&mut self) {
+        self.stop_timer();
+    }
+}
+
+// The numbers that are still possible given the answers so far.
+fn remaining_range(game: &Game) -> (u32, u32) {
+    let config = game.config();
+    let secret = game.secret();
+
+    game.history()
+        .iter()
+        .fold((config.min, config.max), |(low, high), &guess| {
+            if guess < secret {
+                (low.max(guess + 1), high)
+            } else if guess > secret {
+                (low, high.min(guess - 1))
+            } else {
+                (guess, guess)
+            }
+        })
+}
+
+// One cell per slice of the range, solid where the secret can still be.
+fn range_bar(min: u32, max: u32, low: u32, high: u32) -> String {
+    let span = f64::from(max - min) + 1.0;
+
+    (0..BAR)
+        .map(|cell| {
+            let from = f64::from(min) + span * cell as f64 / BAR as f64;
+            let to = f64::from(min) + span * (cell + 1) as f64 / BAR as f64;
+            if to > f64::from(low) && from < f64::from(high) + 1.0 {
+                '█'
+            } else {
+                '░'
+            }
+        })
+        .collect()
+}
+
+fn clock(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
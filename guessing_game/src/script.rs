@@ -0,0 +1,206 @@
+// Scripted and replayed games, for running the game loop without a terminal.
+//
+// `--input guesses.txt --seed 42` plays the guesses in the file, one per
+// line, against the secret drawn from the seed and prints a transcript as
+// JSON lines:
+//
+//     {"type":"start","seed":42,"min":1,"max":100,"max_attempts":8}
+//     {"type":"guess","attempt":1,"guess":50,"outcome":"too_big"}
+//     {"type":"invalid","input":"abc","error":"'abc' is not a number, ..."}
+//     {"type":"end","status":"won","attempts":4,"secret":31}
+//
+// `--replay transcript.jsonl` plays the same guesses again and checks that
+// every line comes out exactly as recorded.
+use std::fmt;
+use std::io::{self, BufRead};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::game::{Game, GameStatus, GuessOutcome};
+use crate::guess::Guess;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Start {
+        seed: u64,
+        min: u32,
+        max: u32,
+        max_attempts: Option<u32>,
+    },
+    Guess {
+        attempt: u32,
+        guess: u32,
+        outcome: GuessOutcome,
+    },
+    // A line that wasn't a valid guess. It doesn't use up an attempt.
+    Invalid {
+        input: String,
+        error: String,
+    },
+    End {
+        status: GameStatus,
+        attempts: u32,
+        secret: u32,
+    },
+}
+
+// Plays the guesses in `input` (blank lines and `#` comments are skipped)
+// until the game is over or the input runs out.
+pub fn run(config: GameConfig, seed: u64, input: impl BufRead) -> io::Result<Vec<Record>> {
+    let mut game = Game::from_seed(config, seed);
+    let mut records = vec![Record::Start {
+        seed,
+        min: config.min,
+        max: config.max,
+        max_attempts: config.max_attempts,
+    }];
+
+    for line in input.lines() {
+        if game.is_over() {
+            break;
+        }
+
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        records.push(play_line(&mut game, line));
+    }
+
+    records.push(end_record(&game));
+    Ok(records)
+}
+
+fn play_line(game: &mut Game, input: &str) -> Record {
+    match Guess::parse(input, game.config()) {
+        Ok(guess) => {
+            let outcome = game.guess(guess.value());
+            Record::Guess {
+                attempt: game.attempts(),
+                guess: guess.value(),
+                outcome,
+            }
+        }
+        Err(err) => Record::Invalid {
+            input: input.to_string(),
+            error: err.to_string(),
+        },
+    }
+}
+
+fn end_record(game: &Game) -> Record {
+    Record::End {
+        status: game.status(),
+        attempts: game.attempts(),
+        secret: game.secret(),
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    // The line isn't a transcript record. Lines are numbered from 1.
+    Parse { line: usize, error: serde_json::Error },
+    MissingStart,
+    Mismatch {
+        line: usize,
+        expected: Record,
+        actual: Record,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not read the transcript: {}", err),
+            ReplayError::Parse { line, error } => {
+                write!(f, "line {} is not a transcript record: {}", line, error)
+            }
+            ReplayError::MissingStart => {
+                write!(f, "the transcript doesn't begin with a start record")
+            }
+            ReplayError::Mismatch {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "line {} differs\n  recorded: {}\n  replayed: {}",
+                line,
+                serde_json::to_string(expected).unwrap_or_default(),
+                serde_json::to_string(actual).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> ReplayError {
+        ReplayError::Io(err)
+    }
+}
+
+pub fn read_transcript(input: impl BufRead) -> Result<Vec<Record>, ReplayError> {
+    let mut records = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str(&line).map_err(|error| ReplayError::Parse {
+            line: index + 1,
+            error,
+        })?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+// Plays a recorded session again from its seed and returns the number of
+// guesses replayed if every record matches.
+pub fn replay(records: &[Record]) -> Result<u32, ReplayError> {
+    let mut game = match records.first() {
+        Some(Record::Start {
+            seed,
+            min,
+            max,
+            max_attempts,
+        }) => {
+            let config = GameConfig {
+                min: *min,
+                max: *max,
+                max_attempts: *max_attempts,
+            };
+            Game::from_seed(config, *seed)
+        }
+        _ => return Err(ReplayError::MissingStart),
+    };
+
+    for (index, expected) in records.iter().enumerate().skip(1) {
+        let actual = match expected {
+            Record::Guess { guess, .. } => play_line(&mut game, &guess.to_string()),
+            Record::Invalid { input, .. } => play_line(&mut game, input),
+            Record::End { .. } => end_record(&game),
+            Record::Start { .. } => expected.clone(),
+        };
+
+        if actual != *expected {
+            return Err(ReplayError::Mismatch {
+                line: index + 1,
+                expected: expected.clone(),
+                actual,
+            });
+        }
+    }
+
+    Ok(game.attempts())
+}
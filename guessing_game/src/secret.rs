@@ -0,0 +1,134 @@
+// A general shape for guessing games: something secret, a way to read a
+// guess for it, and the feedback a guess earns. A `Round` plays one game
+// of any `Secret` and keeps its attempts, so the number game (`Game` in
+// `game.rs`), Mastermind (`mastermind.rs`) and word guessing (`wordle.rs`)
+// all run on the same rules, and `play_round` in `main.rs` plays them all.
+use std::fmt;
+
+use crate::game::GameStatus;
+
+pub trait Secret {
+    type Guess: Clone + fmt::Debug;
+    type Feedback: fmt::Display;
+
+    // A line or two telling the player what to guess and how.
+    fn rules(&self) -> String;
+
+    fn parse_guess(&self, input: &str) -> Result<Self::Guess, String>;
+
+    fn check(&self, guess: &Self::Guess) -> Self::Feedback;
+
+    fn is_solved(&self, feedback: &Self::Feedback) -> bool;
+
+    // The secret written out, for when the player runs out of attempts.
+    fn reveal(&self) -> String;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Turn<F> {
+    Miss(F),
+    Solved(F),
+    // The guess missed and it was the last one allowed.
+    OutOfAttempts(F),
+    // The round was already over, so the guess didn't count.
+    Over,
+}
+
+// One game of any `Secret`.
+#[derive(Debug, Clone)]
+pub struct Round<S: Secret> {
+    secret: S,
+    max_attempts: Option<u32>,
+    attempts: u32,
+    // Every guess made so far, oldest first.
+    history: Vec<S::Guess>,
+    status: GameStatus,
+}
+
+impl<S: Secret> Round<S> {
+    pub fn start(secret: S, max_attempts: Option<u32>) -> Round<S> {
+        Round {
+            secret,
+            max_attempts,
+            attempts: 0,
+            history: Vec::new(),
+            status: GameStatus::InProgress,
+        }
+    }
+
+    // Input that isn't a valid guess is an `Err` and costs nothing.
+    pub fn play(&mut self, input: &str) -> Result<Turn<S::Feedback>, String> {
+        if self.is_over() {
+            return Err(String::from("the game is already over"));
+        }
+
+        let guess = self.secret.parse_guess(input.trim())?;
+        Ok(self.take_turn(guess))
+    }
+
+    pub fn take_turn(&mut self, guess: S::Guess) -> Turn<S::Feedback> {
+        if self.is_over() {
+            return Turn::Over;
+        }
+
+        let feedback = self.secret.check(&guess);
+        self.attempts += 1;
+        self.history.push(guess);
+
+        if self.secret.is_solved(&feedback) {
+            self.status = GameStatus::Won;
+            Turn::Solved(feedback)
+        } else if self.attempts_left() == Some(0) {
+            self.status = GameStatus::Lost;
+            Turn::OutOfAttempts(feedback)
+        } else {
+            Turn::Miss(feedback)
+        }
+    }
+
+    // Uses up attempts without guessing, e.g. to pay for a hint.
+    // Refuses (and returns `false`) if that would leave no guesses at all.
+    pub fn spend_attempts(&mut self, cost: u32) -> bool {
+        if !self.can_spend(cost) {
+            return false;
+        }
+
+        self.attempts += cost;
+        true
+    }
+
+    pub fn can_spend(&self, cost: u32) -> bool {
+        match self.attempts_left() {
+            _ if self.is_over() => false,
+            Some(left) => cost < left,
+            None => true,
+        }
+    }
+
+    // What is being guessed, as opposed to `Game::secret`, which is the
+    // number itself.
+    pub fn puzzle(&self) -> &S {
+        &self.secret
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn history(&self) -> &[S::Guess] {
+        &self.history
+    }
+
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts
+            .map(|max_attempts| max_attempts.saturating_sub(self.attempts))
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status != GameStatus::InProgress
+    }
+}
@@ -0,0 +1,340 @@
+// Multiplayer over TCP: the server holds one secret number and the players
+// who connect take turns guessing it. Everything is plain text, one message
+// per line, so `nc localhost PORT` is enough to join a game.
+//
+// Client to server:
+//
+//     NAME <name>       pick the name shown to the other players
+//     GUESS <number>    guess when it's your turn (a bare number works too)
+//     QUIT              leave the game
+//
+// Server to client:
+//
+//     WELCOME <name> <min> <max>
+//     JOINED <name> / LEFT <name> / RENAMED <old> <new>
+//     TURN <name>
+//     HINT <name> <number> TOO_SMALL|TOO_BIG
+//     WIN <name> <number>
+//     SCORE <rank> <name> <attempts>   once per player after the win, then END
+//     ERR <message>
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::GameConfig;
+use crate::game::{Game, GuessOutcome};
+use crate::guess::Guess;
+
+// One line of the final scoreboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub name: String,
+    pub attempts: u32,
+    pub winner: bool,
+}
+
+struct Player {
+    id: usize,
+    name: String,
+    attempts: u32,
+    // `None` once the player has disconnected.
+    stream: Option<TcpStream>,
+}
+
+struct Table {
+    game: Game,
+    players: Vec<Player>,
+    // The id of the player whose turn it is.
+    turn: Option<usize>,
+    winner: Option<usize>,
+}
+
+impl Table {
+    fn player(&mut self, id: usize) -> &mut Player {
+        self.players
+            .iter_mut()
+            .find(|player| player.id == id)
+            .expect("every connection has a player")
+    }
+
+    fn name(&self, id: usize) -> &str {
+        self.players
+            .iter()
+            .find(|player| player.id == id)
+            .map(|player| player.name.as_str())
+            .unwrap_or("?")
+    }
+
+    fn send(&mut self, id: usize, message: &str) {
+        if let Some(stream) = self.player(id).stream.as_mut() {
+            // A failed write means the player is gone; their own
+            // connection thread notices and cleans up.
+            let _ = writeln!(stream, "{}", message);
+        }
+    }
+
+    fn broadcast(&mut self, message: &str) {
+        for player in self.players.iter_mut() {
+            if let Some(stream) = player.stream.as_mut() {
+                let _ = writeln!(stream, "{}", message);
+            }
+        }
+    }
+
+    // Hands the turn to the next connected player after `from`, in join order.
+    fn advance_turn(&mut self, from: usize) {
+        let connected: Vec<usize> = self
+            .players
+            .iter()
+            .filter(|player| player.stream.is_some())
+            .map(|player| player.id)
+            .collect();
+
+        self.turn = connected
+            .iter()
+            .find(|&&id| id > from)
+            .or_else(|| connected.first())
+            .copied();
+
+        if let Some(turn) = self.turn {
+            let message = format!("TURN {}", self.name(turn));
+            self.broadcast(&message);
+        }
+    }
+
+    fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .players
+            .iter()
+            .map(|player| Standing {
+                name: player.name.clone(),
+                attempts: player.attempts,
+                winner: self.winner == Some(player.id),
+            })
+            .collect();
+
+        // The winner first, then everyone else by how few guesses they used.
+        standings.sort_by_key(|standing| (!standing.winner, standing.attempts));
+        standings
+    }
+
+    fn finish(&mut self) {
+        let standings = self.standings();
+        for (rank, standing) in standings.iter().enumerate() {
+            let message = format!("SCORE {} {} {}", rank + 1, standing.name, standing.attempts);
+            self.broadcast(&message);
+        }
+        self.broadcast("END");
+
+        for player in self.players.iter_mut() {
+            if let Some(stream) = player.stream.take() {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+        }
+    }
+}
+
+// Runs one game on `listener` and returns the final scoreboard once
+// somebody has guessed the secret. The game's attempt limit is ignored:
+// players keep taking turns until there is a winner.
+pub fn serve(listener: TcpListener, game: Game) -> io::Result<Vec<Standing>> {
+    let config = GameConfig {
+        max_attempts: None,
+        ..*game.config()
+    };
+    let game = Game::with_secret(config, game.secret());
+
+    let table = Arc::new(Mutex::new(Table {
+        game,
+        players: Vec::new(),
+        turn: None,
+        winner: None,
+    }));
+
+    // Accepting without blocking lets the loop notice when the game is won.
+    listener.set_nonblocking(true)?;
+    let mut next_id = 1;
+    let mut handles = Vec::new();
+
+    while table.lock().unwrap().winner.is_none() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                let id = next_id;
+                next_id += 1;
+
+                let table = Arc::clone(&table);
+                handles.push(thread::spawn(move || handle_client(table, id, stream)));
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    for handle in handles {
+        let _ = handle.join();
+    }
+
+    let standings = table.lock().unwrap().standings();
+    Ok(standings)
+}
+
+fn handle_client(table: Arc<Mutex<Table>>, id: usize, stream: TcpStream) {
+    let reader = match stream.try_clone() {
+        Ok(reader) => BufReader::new(reader),
+        Err(_) => return,
+    };
+
+    {
+        let mut table = table.lock().unwrap();
+        // Someone accepted just before the winning guess has missed the
+        // game, and must not keep `serve` waiting for them.
+        if table.winner.is_some() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+            return;
+        }
+
+        let name = format!("player{}", id);
+        let (min, max) = (table.game.config().min, table.game.config().max);
+
+        table.players.push(Player {
+            id,
+            name: name.clone(),
+            attempts: 0,
+            stream: Some(stream),
+        });
+        table.send(id, &format!("WELCOME {} {} {}", name, min, max));
+        table.broadcast(&format!("JOINED {}", name));
+
+        match table.turn {
+            Some(turn) => {
+                let message = format!("TURN {}", table.name(turn));
+                table.send(id, &message);
+            }
+            None => {
+                table.turn = Some(id);
+                table.broadcast(&format!("TURN {}", name));
+            }
+        }
+    }
+
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        let mut table = table.lock().unwrap();
+        if table.winner.is_some() {
+            break;
+        }
+
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("").to_uppercase();
+        let argument = words.collect::<Vec<_>>().join("_");
+
+        match command.as_str() {
+            "" => {}
+            "NAME" if argument.is_empty() => table.send(id, "ERR NAME needs a name"),
+            "NAME" => {
+                let old = std::mem::replace(&mut table.player(id).name, argument.clone());
+                table.broadcast(&format!("RENAMED {} {}", old, argument));
+            }
+            "QUIT" => break,
+            "GUESS" => take_guess(&mut table, id, &argument),
+            _ => take_guess(&mut table, id, &command),
+        }
+
+        if table.winner.is_some() {
+            table.finish();
+            break;
+        }
+    }
+
+    let mut table = table.lock().unwrap();
+    if table.winner.is_some() {
+        return;
+    }
+
+    let player = table.player(id);
+    if player.stream.take().is_some() {
+        let name = player.name.clone();
+        table.broadcast(&format!("LEFT {}", name));
+
+        if table.turn == Some(id) {
+            table.advance_turn(id);
+        }
+    }
+}
+
+fn take_guess(table: &mut Table, id: usize, input: &str) {
+    if table.turn != Some(id) {
+        let message = match table.turn {
+            Some(turn) => format!("ERR not your turn, waiting for {}", table.name(turn)),
+            None => String::from("ERR not your turn"),
+        };
+        table.send(id, &message);
+        return;
+    }
+
+    let guess = match Guess::parse(input, table.game.config()) {
+        Ok(guess) => guess.value(),
+        Err(err) => {
+            table.send(id, &format!("ERR {}", err));
+            return;
+        }
+    };
+
+    table.player(id).attempts += 1;
+    let name = table.name(id).to_string();
+
+    let hint = match table.game.guess(guess) {
+        GuessOutcome::Correct => {
+            table.winner = Some(id);
+            table.broadcast(&format!("WIN {} {}", name, guess));
+            return;
+        }
+        GuessOutcome::TooSmall => "TOO_SMALL",
+        GuessOutcome::TooBig => "TOO_BIG",
+        GuessOutcome::OutOfAttempts => unreachable!("the shared game has no attempt limit"),
+    };
+    table.broadcast(&format!("HINT {} {} {}", name, guess, hint));
+    table.advance_turn(id);
+}
+
+// A minimal client: lines typed by the player go to the server and
+// everything the server sends is handed to `output` as it arrives.
+pub fn connect(
+    address: &str,
+    input: impl BufRead,
+    mut output: impl FnMut(&str) + Send + 'static,
+) -> io::Result<()> {
+    let mut stream = TcpStream::connect(address)?;
+    let reader = BufReader::new(stream.try_clone()?);
+
+    let printer = thread::spawn(move || {
+        for line in reader.lines() {
+            match line {
+                Ok(line) => output(&line),
+                Err(_) => break,
+            }
+        }
+    });
+
+    for line in input.lines() {
+        if writeln!(stream, "{}", line?).is_err() {
+            break;
+        }
+        if printer.is_finished() {
+            break;
+        }
+    }
+
+    let _ = stream.shutdown(std::net::Shutdown::Write);
+    let _ = printer.join();
+    Ok(())
+}
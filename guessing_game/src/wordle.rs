@@ -0,0 +1,140 @@
+// Word guessing in the style of Wordle: find a five letter word, and after
+// every guess each letter is marked as in the right spot, somewhere else in
+// the word, or not in the word at all.
+use std::fmt;
+
+use rand::Rng;
+
+use crate::secret::Secret;
+
+pub const LENGTH: usize = 5;
+
+// The secret is always one of these. Guesses can be any five letters.
+pub const WORDS: [&str; 60] = [
+    "apple", "beach", "brain", "bread", "brush", "chair", "chest", "chord", "click", "clock",
+    "cloud", "dance", "diary", "drink", "earth", "feast", "field", "fruit", "glass", "grape",
+    "green", "ghost", "heart", "house", "juice", "light", "lemon", "money", "music", "night",
+    "ocean", "party", "piano", "pilot", "plane", "plant", "radio", "river", "robot", "salad",
+    "sheep", "shirt", "shoes", "smile", "snake", "space", "spoon", "storm", "sugar", "table",
+    "tiger", "toast", "train", "truck", "voice", "water", "whale", "world", "write", "zebra",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    // Right letter in the right spot.
+    Correct,
+    // The letter is in the word, but somewhere else.
+    Present,
+    Absent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marks {
+    pub letters: Vec<char>,
+    pub marks: Vec<Mark>,
+}
+
+impl fmt::Display for Marks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let word: Vec<String> = self
+            .letters
+            .iter()
+            .map(|c| c.to_ascii_uppercase().to_string())
+            .collect();
+        let squares: String = self
+            .marks
+            .iter()
+            .map(|mark| match mark {
+                Mark::Correct => '🟩',
+                Mark::Present => '🟨',
+                Mark::Absent => '⬛',
+            })
+            .collect();
+        write!(f, "{}  {}", word.join(" "), squares)
+    }
+}
+
+pub struct Wordle {
+    word: Vec<char>,
+}
+
+impl Wordle {
+    pub fn new(word: &str) -> Wordle {
+        Wordle {
+            word: word.to_lowercase().chars().collect(),
+        }
+    }
+
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Wordle {
+        Wordle::new(WORDS[rng.gen_range(0..WORDS.len())])
+    }
+}
+
+impl Secret for Wordle {
+    type Guess = Vec<char>;
+    type Feedback = Marks;
+
+    fn rules(&self) -> String {
+        String::from(
+            "Guess the five letter word.\n\
+             🟩 right letter, right spot  🟨 in the word, wrong spot  ⬛ not in the word",
+        )
+    }
+
+    fn parse_guess(&self, input: &str) -> Result<Vec<char>, String> {
+        let letters: Vec<char> = input.to_lowercase().chars().collect();
+
+        if letters.len() != LENGTH {
+            return Err(format!(
+                "the word has {} letters, '{}' has {}",
+                LENGTH,
+                input,
+                letters.len()
+            ));
+        }
+
+        if letters.iter().any(|c| !c.is_ascii_lowercase()) {
+            return Err(format!("'{}' should only have letters a to z", input));
+        }
+
+        Ok(letters)
+    }
+
+    fn check(&self, guess: &Vec<char>) -> Marks {
+        let mut marks = vec![Mark::Absent; LENGTH];
+        // Letters of the secret not used up by an exact match, so a letter
+        // guessed twice is only marked present as often as it really is.
+        let mut unmatched: Vec<char> = Vec::new();
+
+        for i in 0..LENGTH {
+            if guess[i] == self.word[i] {
+                marks[i] = Mark::Correct;
+            } else {
+                unmatched.push(self.word[i]);
+            }
+        }
+
+        for i in 0..LENGTH {
+            if marks[i] == Mark::Correct {
+                continue;
+            }
+            if let Some(position) = unmatched.iter().position(|&c| c == guess[i]) {
+                unmatched.swap_remove(position);
+                marks[i] = Mark::Present;
+            }
+        }
+
+        Marks {
+            letters: guess.clone(),
+            marks,
+        }
+    }
+
+    fn is_solved(&self, feedback: &Marks) -> bool {
+        feedback.marks.iter().all(|&mark| mark == Mark::Correct)
+    }
+
+    fn reveal(&self) -> String {
+        self.word.iter().collect::<String>().to_uppercase()
+    }
+}
@@ -0,0 +1,74 @@
+// Plays a scripted session through the binary, with no terminal, then
+// checks that its transcript replays the same way.
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use guessing_game::script::{self, Record};
+use guessing_game::{GameStatus, GuessOutcome};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_guessing_game"))
+        .args(args)
+        .output()
+        .expect("the game runs")
+}
+
+fn scratch(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("guessing_game-{}-{}", std::process::id(), name))
+}
+
+fn write_transcript(path: &PathBuf, records: &[Record]) {
+    let lines: Vec<String> = records
+        .iter()
+        .map(|record| serde_json::to_string(record).unwrap())
+        .collect();
+    fs::write(path, lines.join("\n")).unwrap();
+}
+
+#[test]
+fn a_scripted_session_replays_the_same_way() {
+    // Eight guesses use up the Normal game's attempts, so it ends either way.
+    let guesses = scratch("guesses.txt");
+    fs::write(&guesses, "# a few guesses\n50\nfifty\n25\n75\n12\n37\n62\n87\n6\n").unwrap();
+
+    let played = run(&["--seed", "42", "--input", guesses.to_str().unwrap()]);
+    assert!(played.status.success());
+    let mut records = script::read_transcript(&played.stdout[..]).unwrap();
+
+    assert!(matches!(records.first(), Some(Record::Start { seed: 42, .. })));
+    assert!(records
+        .iter()
+        .any(|record| matches!(record, Record::Invalid { input, .. } if input == "fifty")));
+    let attempts = match records.last() {
+        Some(Record::End { status, attempts, .. }) => {
+            assert_ne!(*status, GameStatus::InProgress);
+            *attempts
+        }
+        other => panic!("the transcript ends with {:?}", other),
+    };
+
+    let transcript = scratch("transcript.jsonl");
+    fs::write(&transcript, &played.stdout).unwrap();
+    let replayed = run(&["--replay", transcript.to_str().unwrap()]);
+    assert!(replayed.status.success());
+    let message = String::from_utf8(replayed.stdout).unwrap();
+    assert!(message.contains(&format!("matches ({} attempts)", attempts)));
+
+    // A transcript that no longer matches its seed fails the replay.
+    for record in records.iter_mut() {
+        if let Record::Guess { outcome, .. } = record {
+            *outcome = match outcome {
+                GuessOutcome::TooSmall => GuessOutcome::TooBig,
+                _ => GuessOutcome::TooSmall,
+            };
+            break;
+        }
+    }
+    write_transcript(&transcript, &records);
+    assert!(!run(&["--replay", transcript.to_str().unwrap()]).status.success());
+
+    let _ = fs::remove_file(guesses);
+    let _ = fs::remove_file(transcript);
+}
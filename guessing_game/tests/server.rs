@@ -0,0 +1,54 @@
+// Plays a whole multiplayer game over loopback.
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use guessing_game::server;
+use guessing_game::{Game, GameConfig};
+
+fn read_until(reader: &mut impl BufRead, prefix: &str) -> String {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).expect("the server answers");
+        assert!(read > 0, "the server hung up before sending {}", prefix);
+        if line.starts_with(prefix) {
+            return line.trim_end().to_string();
+        }
+    }
+}
+
+#[test]
+fn the_attempt_limit_does_not_stop_a_shared_game() {
+    let config = GameConfig {
+        min: 1,
+        max: 100,
+        max_attempts: Some(2),
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || server::serve(listener, Game::with_secret(config, 100)));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    read_until(&mut reader, "TURN");
+
+    for guess in 1..=3 {
+        writeln!(stream, "GUESS {}", guess).unwrap();
+        assert_eq!(
+            read_until(&mut reader, "HINT"),
+            format!("HINT player1 {} TOO_SMALL", guess)
+        );
+    }
+
+    writeln!(stream, "GUESS 100").unwrap();
+    assert_eq!(read_until(&mut reader, "WIN"), "WIN player1 100");
+    assert_eq!(read_until(&mut reader, "SCORE"), "SCORE 1 player1 4");
+
+    let standings = server.join().unwrap().unwrap();
+    assert_eq!(standings.len(), 1);
+    assert!(standings[0].winner);
+    assert_eq!(standings[0].attempts, 4);
+}